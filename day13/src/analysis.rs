@@ -0,0 +1,246 @@
+// statistics on how the blocks get broken, and the score table read from the game's memory
+//
+// The game keeps the tiles in memory as a grid, row by row, followed by a table of the same size
+// holding the points for each block. The block at (x, y) scores the table entry at
+// ((x * height + y) * multiplier + offset) % (width * height).
+
+use crate::emulator::Program;
+use crate::game::Game;
+use crate::player::{Player, Tracker};
+use crate::screen::{Screen, BLOCK};
+use std::collections::HashSet;
+use std::fmt::{Display, Error, Formatter};
+
+pub struct ScoreTable {
+    pub address: usize,
+    // where the game keeps the score
+    pub score_address: usize,
+    pub multiplier: usize,
+    pub offset: usize,
+    height: usize,
+    entries: Vec<i64>,
+    blocks: Vec<(i64, i64)>,
+}
+
+impl ScoreTable {
+    // finds the table by watching the writes that update the score, playing only until the
+    // layout of the table is pinned down
+    pub fn locate(program: Program) -> Result<ScoreTable, &'static str> {
+        let mut game = Game::new(program);
+        game.watch_writes();
+        game.run_frame()?;
+
+        let (width, height) = game.screen.size();
+        let size = (width * height) as usize;
+        let grid_address = find_grid(game.memory(), &game.screen).ok_or("tile grid not found")?;
+        let address = grid_address + size;
+        let entries = game
+            .memory()
+            .get(address..address + size)
+            .ok_or("score table out of memory")?
+            .to_vec();
+        let mut blocks: Vec<(i64, i64)> = game
+            .screen
+            .grid
+            .iter()
+            .filter(|(_, &tile)| tile == BLOCK)
+            .map(|(&pos, _)| pos)
+            .collect();
+        blocks.sort();
+
+        // (x * height + y, table index) of each block seen scoring on its own
+        let mut seen = Vec::new();
+        let mut score_address = 0;
+        let mut remaining: HashSet<(i64, i64)> = blocks.iter().cloned().collect();
+        let mut player = Tracker;
+        game.take_writes();
+        while !game.halted {
+            game.send(player.joystick(&game)?)?;
+            let updates = game.run_frame()?;
+            let writes = game.take_writes();
+
+            let broken: Vec<(i64, i64)> = updates
+                .iter()
+                .filter(|&&(x, y, tile)| tile != BLOCK && remaining.remove(&(x, y)))
+                .map(|&(x, y, _)| (x, y))
+                .collect();
+            let score = game.screen.score;
+            let score_writes: Vec<_> = writes.iter().filter(|w| w.value == score).collect();
+            if let ([(x, y)], [write]) = (&broken[..], &score_writes[..]) {
+                let entry = write
+                    .operands
+                    .iter()
+                    .map(|&(addr, _)| addr as usize)
+                    .find(|addr| (address..address + size).contains(addr));
+                if let Some(entry) = entry {
+                    score_address = write.address as usize;
+                    seen.push(((x * height + y) as usize, entry - address));
+                }
+            }
+
+            let layouts = fit_layouts(&seen, size);
+            if let [(multiplier, offset)] = layouts[..] {
+                return Ok(ScoreTable {
+                    address,
+                    score_address,
+                    multiplier,
+                    offset,
+                    height: height as usize,
+                    entries,
+                    blocks,
+                });
+            }
+        }
+        Err("score table layout not determined")
+    }
+
+    pub fn points(&self, x: i64, y: i64) -> i64 {
+        let pos = x as usize * self.height + y as usize;
+        self.entries[(pos * self.multiplier + self.offset) % self.entries.len()]
+    }
+
+    // the score after breaking every block
+    pub fn final_score(&self) -> i64 {
+        self.blocks.iter().map(|&(x, y)| self.points(x, y)).sum()
+    }
+}
+
+// finds the address of the tile grid by matching it against the first frame drawn
+fn find_grid(memory: &[i64], screen: &Screen) -> Option<usize> {
+    let (width, height) = screen.size();
+    let size = (width * height) as usize;
+    (0..memory.len().saturating_sub(size)).find(|&address| {
+        (0..height).all(|y| {
+            (0..width).all(|x| memory[address + (y * width + x) as usize] == screen.tile(x, y))
+        })
+    })
+}
+
+// returns every (multiplier, offset) that maps the seen positions to their table indices
+fn fit_layouts(seen: &[(usize, usize)], size: usize) -> Vec<(usize, usize)> {
+    let (pos, index) = match seen.first() {
+        Some(&first) => first,
+        None => return Vec::new(),
+    };
+    (0..size)
+        .map(|multiplier| (multiplier, (index + size - pos * multiplier % size) % size))
+        .filter(|&(multiplier, offset)| {
+            seen.iter()
+                .all(|&(pos, index)| (pos * multiplier + offset) % size == index)
+        })
+        .collect()
+}
+
+pub struct Hit {
+    pub frame: usize,
+    pub pos: (i64, i64),
+    pub points: i64,
+}
+
+pub struct Stats {
+    pub hits: Vec<Hit>,
+    pub frames: usize,
+    pub paddle_moves: usize,
+    pub score: i64,
+}
+
+// plays the game to the end, noting when each block breaks
+pub fn play(
+    program: Program,
+    player: &mut dyn Player,
+    table: &ScoreTable,
+) -> Result<Stats, &'static str> {
+    let mut game = Game::new(program);
+    let mut blocks: HashSet<(i64, i64)> = table.blocks.iter().cloned().collect();
+    let mut hits = Vec::new();
+    let mut paddle_moves = 0;
+    let mut frame = 0;
+
+    loop {
+        for (x, y, tile) in game.run_frame()? {
+            if tile != BLOCK && blocks.remove(&(x, y)) {
+                hits.push(Hit {
+                    frame,
+                    pos: (x, y),
+                    points: table.points(x, y),
+                });
+            }
+        }
+        if game.halted {
+            break;
+        }
+
+        let joystick = player.joystick(&game)?;
+        if joystick != 0 {
+            paddle_moves += 1;
+        }
+        game.send(joystick)?;
+        frame += 1;
+    }
+
+    Ok(Stats {
+        hits,
+        frames: frame,
+        paddle_moves,
+        score: game.screen.score,
+    })
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        writeln!(
+            f,
+            "{} frames, {} paddle moves, {} blocks broken, score {}",
+            self.frames,
+            self.paddle_moves,
+            self.hits.len(),
+            self.score
+        )?;
+
+        let gaps: Vec<usize> = self
+            .hits
+            .windows(2)
+            .map(|pair| pair[1].frame - pair[0].frame)
+            .collect();
+        if let (Some(min), Some(max)) = (gaps.iter().min(), gaps.iter().max()) {
+            let mean = gaps.iter().sum::<usize>() as f64 / gaps.len() as f64;
+            writeln!(
+                f,
+                "frames between hits: min {}, mean {:.1}, max {}",
+                min, mean, max
+            )?;
+        }
+
+        for (i, hit) in self.hits.iter().enumerate() {
+            writeln!(
+                f,
+                "{:4}. frame {:5}: block ({}, {}) for {} points",
+                i + 1,
+                hit.frame,
+                hit.pos.0,
+                hit.pos.1,
+                hit.points
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fit_layouts;
+
+    #[test]
+    fn test_fit_layouts() {
+        // (pos * 2 + 1) % 7
+        assert_eq!(fit_layouts(&[(1, 3), (2, 5)], 7), vec![(2, 1)]);
+        assert_eq!(fit_layouts(&[(1, 3), (2, 5), (5, 4)], 7), vec![(2, 1)]);
+        assert_eq!(fit_layouts(&[(1, 3), (2, 6)], 7), vec![(3, 0)]);
+
+        // a single sample fits any multiplier
+        assert_eq!(fit_layouts(&[(1, 3)], 7).len(), 7);
+        // with a table size that is not prime, 2 * multiplier = 4 (mod 8) has two answers
+        assert_eq!(fit_layouts(&[(2, 1), (4, 5)], 8), vec![(2, 5), (6, 5)]);
+        assert_eq!(fit_layouts(&[], 8), vec![]);
+    }
+}
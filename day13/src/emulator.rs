@@ -1,5 +1,6 @@
 use std::iter;
 
+#[derive(Clone, Copy)]
 enum ParameterMode {
     Position,
     Immediate,
@@ -83,10 +84,22 @@ impl Program {
     }
 }
 
+// a memory write made by an add or multiply, with the (address, value) of each operand read
+// from memory
+#[derive(Clone)]
+pub struct Write {
+    pub address: i64,
+    pub value: i64,
+    pub operands: Vec<(i64, i64)>,
+}
+
+#[derive(Clone)]
 pub struct Emulator {
     memory: Vec<i64>,
     ip: i64,
     relative_base: i64,
+    instructions: u64,
+    writes: Option<Vec<Write>>,
 }
 
 impl Emulator {
@@ -95,9 +108,35 @@ impl Emulator {
             memory: program.memory,
             ip: 0,
             relative_base: 0,
+            instructions: 0,
+            writes: None,
         }
     }
 
+    /// starts logging arithmetic writes to memory
+    pub fn watch_writes(&mut self) {
+        self.writes = Some(Vec::new());
+    }
+
+    /// returns the writes logged since the last call
+    pub fn take_writes(&mut self) -> Vec<Write> {
+        self.writes.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    pub fn memory(&self) -> &[i64] {
+        &self.memory
+    }
+
+    /// number of instructions executed so far
+    pub fn instructions(&self) -> u64 {
+        self.instructions
+    }
+
+    /// returns true if the next instruction reads input
+    pub fn next_is_input(&self) -> bool {
+        self.get(self.ip) % 100 == 3
+    }
+
     fn get(&self, address: i64) -> i64 {
         let address = address as usize;
         if address >= self.memory.len() {
@@ -111,7 +150,7 @@ impl Emulator {
         let address = address as usize;
         if address >= self.memory.len() {
             self.memory
-                .extend(iter::repeat(0).take(address - self.memory.len() + 1))
+                .extend(iter::repeat_n(0, address - self.memory.len() + 1))
         }
         self.memory[address] = value;
     }
@@ -125,6 +164,41 @@ impl Emulator {
         }
     }
 
+    fn get_arg_address(&self, n: i64, mode: ParameterMode) -> Option<i64> {
+        let arg = self.get(self.ip + n);
+        match mode {
+            ParameterMode::Immediate => None,
+            ParameterMode::Position => Some(arg),
+            ParameterMode::Relative => Some(arg + self.relative_base),
+        }
+    }
+
+    // reads the operands of an arithmetic instruction if writes are being logged
+    fn watched_operands(
+        &self,
+        p1_mode: ParameterMode,
+        p2_mode: ParameterMode,
+    ) -> Option<Vec<(i64, i64)>> {
+        self.writes.as_ref()?;
+        Some(
+            [(1, p1_mode), (2, p2_mode)]
+                .iter()
+                .filter_map(|&(n, mode)| self.get_arg_address(n, mode))
+                .map(|addr| (addr, self.get(addr)))
+                .collect(),
+        )
+    }
+
+    fn log_write(&mut self, address: i64, value: i64, operands: Option<Vec<(i64, i64)>>) {
+        if let (Some(writes), Some(operands)) = (&mut self.writes, operands) {
+            writes.push(Write {
+                address,
+                value,
+                operands,
+            });
+        }
+    }
+
     fn get_arg_dest(&self, n: i64, mode: ParameterMode) -> Result<i64, &'static str> {
         let arg = self.get(self.ip + n);
         Ok(match mode {
@@ -142,20 +216,25 @@ impl Emulator {
     ) -> Result<bool, &'static str> {
         let instr_code = self.get(self.ip);
         let instr = Instruction::parse(instr_code)?;
+        self.instructions += 1;
 
         match instr.opcode {
             Opcode::Add => {
                 let arg1 = self.get_arg_val(1, instr.p1_mode);
                 let arg2 = self.get_arg_val(2, instr.p2_mode);
                 let res_addr = self.get_arg_dest(3, instr.p3_mode)?;
+                let operands = self.watched_operands(instr.p1_mode, instr.p2_mode);
                 self.store(res_addr, arg1 + arg2);
+                self.log_write(res_addr, arg1 + arg2, operands);
                 self.ip += 4;
             }
             Opcode::Multiply => {
                 let arg1 = self.get_arg_val(1, instr.p1_mode);
                 let arg2 = self.get_arg_val(2, instr.p2_mode);
                 let res_addr = self.get_arg_dest(3, instr.p3_mode)?;
+                let operands = self.watched_operands(instr.p1_mode, instr.p2_mode);
                 self.store(res_addr, arg1 * arg2);
+                self.log_write(res_addr, arg1 * arg2, operands);
                 self.ip += 4;
             }
             Opcode::Input => {
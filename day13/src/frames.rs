@@ -0,0 +1,145 @@
+// export frames of the game as numbered image files
+
+use crate::screen::{Palette, Screen};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+pub enum Format {
+    Ppm(Palette),
+    Pbm,
+}
+
+pub struct FrameExporter {
+    dir: PathBuf,
+    format: Format,
+    scale: usize,
+    // write every nth frame
+    every: usize,
+    // only write frames where the score changed
+    score_changes: bool,
+
+    frame: usize,
+    last_score: Option<i64>,
+}
+
+impl FrameExporter {
+    pub fn new(dir: impl Into<PathBuf>, format: Format, scale: usize) -> Self {
+        FrameExporter {
+            dir: dir.into(),
+            format,
+            scale,
+            every: 1,
+            score_changes: false,
+            frame: 0,
+            last_score: None,
+        }
+    }
+
+    pub fn every(mut self, every: usize) -> Self {
+        self.every = every.max(1);
+        self
+    }
+
+    pub fn score_changes(mut self) -> Self {
+        self.score_changes = true;
+        self
+    }
+
+    // call at the end of every frame
+    pub fn frame(&mut self, screen: &Screen) -> io::Result<()> {
+        let frame = self.frame;
+        self.frame += 1;
+
+        let score_changed = self.last_score != Some(screen.score);
+        self.last_score = Some(screen.score);
+        if !frame.is_multiple_of(self.every) || (self.score_changes && !score_changed) {
+            return Ok(());
+        }
+
+        let (ext, data) = match &self.format {
+            Format::Ppm(palette) => ("ppm", screen.to_ppm(palette, self.scale)),
+            Format::Pbm => ("pbm", screen.to_pbm(self.scale)),
+        };
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.dir.join(format!("frame_{:05}.{}", frame, ext)), data)
+    }
+}
+
+// parses a palette of five comma separated RRGGBB colors, one for each tile type
+pub fn parse_palette(s: &str) -> Result<Palette, &'static str> {
+    let colors = s
+        .split(',')
+        .map(|color| {
+            let color = u32::from_str_radix(color.trim().trim_start_matches('#'), 16)
+                .map_err(|_| "invalid color")?;
+            Ok([(color >> 16) as u8, (color >> 8) as u8, color as u8])
+        })
+        .collect::<Result<Vec<[u8; 3]>, &'static str>>()?;
+
+    let mut palette = [[0; 3]; 5];
+    if colors.len() != palette.len() {
+        return Err("palette needs a color for each tile type");
+    }
+    palette.copy_from_slice(&colors);
+    Ok(palette)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_palette, Format, FrameExporter};
+    use crate::screen::{Screen, DEFAULT_PALETTE, WALL};
+    use std::env;
+    use std::fs;
+
+    #[test]
+    fn test_parse_palette() {
+        assert_eq!(
+            parse_palette("#000000, 808080,E6A028,3cb4e6,#f03c3c"),
+            Ok(DEFAULT_PALETTE)
+        );
+        assert_eq!(
+            parse_palette("000000,808080,e6a028,3cb4e6,zz"),
+            Err("invalid color")
+        );
+        assert_eq!(
+            parse_palette("000000,808080,e6a028,3cb4e6"),
+            Err("palette needs a color for each tile type")
+        );
+    }
+
+    // returns the numbers of the frames written for the scores of each frame
+    fn export(name: &str, exporter: impl Fn(FrameExporter) -> FrameExporter) -> Vec<usize> {
+        let dir = env::temp_dir().join(format!("day13_{}_{}", name, std::process::id()));
+        let mut frames = exporter(FrameExporter::new(&dir, Format::Pbm, 1));
+        let mut screen = Screen::new();
+        screen.process_triple(0, 0, WALL);
+        for &score in [0, 0, 5, 5, 5, 7, 7].iter() {
+            screen.score = score;
+            frames.frame(&screen).unwrap();
+        }
+
+        let mut written: Vec<usize> = fs::read_dir(&dir)
+            .map(|entries| {
+                entries
+                    .map(|entry| {
+                        let name = entry.unwrap().file_name().into_string().unwrap();
+                        name["frame_".len().."frame_00000".len()].parse().unwrap()
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        written.sort();
+        let _ = fs::remove_dir_all(&dir);
+        written
+    }
+
+    #[test]
+    fn test_export_filters() {
+        assert_eq!(export("all", |e| e), vec![0, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(export("every", |e| e.every(3)), vec![0, 3, 6]);
+        assert_eq!(export("every_0", |e| e.every(0)).len(), 7);
+        assert_eq!(export("score", |e| e.score_changes()), vec![0, 2, 5]);
+        assert_eq!(export("both", |e| e.every(2).score_changes()), vec![0, 2]);
+    }
+}
@@ -0,0 +1,80 @@
+// run the arcade one frame at a time
+
+use crate::emulator::{Emulator, Program, Write};
+use crate::screen::Screen;
+
+#[derive(Clone)]
+pub struct Game {
+    emu: Emulator,
+    pub screen: Screen,
+    output_buffer: Vec<i64>,
+    pub halted: bool,
+    // number of joystick positions sent
+    pub inputs: usize,
+}
+
+impl Game {
+    // starts the game in free play mode
+    pub fn new(program: Program) -> Self {
+        let mut emu = Emulator::new(program);
+        emu.store(0, 2);
+        Game {
+            emu,
+            screen: Screen::new(),
+            output_buffer: Vec::with_capacity(3),
+            halted: false,
+            inputs: 0,
+        }
+    }
+
+    pub fn instructions(&self) -> u64 {
+        self.emu.instructions()
+    }
+
+    pub fn watch_writes(&mut self) {
+        self.emu.watch_writes();
+    }
+
+    // returns the memory writes logged since the last call, once watching
+    pub fn take_writes(&mut self) -> Vec<Write> {
+        self.emu.take_writes()
+    }
+
+    pub fn memory(&self) -> &[i64] {
+        self.emu.memory()
+    }
+
+    // runs until the game reads the joystick or halts, returning the tile updates drawn
+    pub fn run_frame(&mut self) -> Result<Vec<(i64, i64, i64)>, &'static str> {
+        let mut updates = Vec::new();
+        while !self.halted && !self.emu.next_is_input() {
+            let screen = &mut self.screen;
+            let output_buffer = &mut self.output_buffer;
+            self.halted = self.emu.step(
+                || Err("no input"),
+                |output| {
+                    output_buffer.push(output);
+                    if output_buffer.len() == 3 {
+                        let (x, y, val) = (output_buffer[0], output_buffer[1], output_buffer[2]);
+                        screen.process_triple(x, y, val);
+                        updates.push((x, y, val));
+                        output_buffer.clear();
+                    }
+                    Ok(())
+                },
+            )?;
+        }
+        Ok(updates)
+    }
+
+    // sends a joystick position: -1 for left, 0 for neutral and 1 for right
+    pub fn send(&mut self, joystick: i64) -> Result<(), &'static str> {
+        if self.halted {
+            return Err("game is over");
+        }
+        self.emu
+            .step(|| Ok(joystick), |_| Err("expected input instruction"))?;
+        self.inputs += 1;
+        Ok(())
+    }
+}
@@ -1,56 +1,19 @@
+mod analysis;
 mod emulator;
-
-use std::cmp::Ordering;
-use std::collections::HashMap;
-
-struct Screen {
-    grid: HashMap<(i64, i64), i64>,
-    score: i64,
-
-    ball_pos: (i64, i64),
-    paddle_pos: (i64, i64),
-}
-
-impl Screen {
-    fn new() -> Self {
-        Screen {
-            grid: HashMap::new(),
-            score: 0,
-            ball_pos: (0, 0),
-            paddle_pos: (0, 0),
-        }
-    }
-
-    fn process_triple(&mut self, x: i64, y: i64, val: i64) {
-        if x == -1 && y == 0 {
-            self.score = val;
-        } else {
-            self.grid.insert((x, y), val);
-            if val == 4 {
-                self.ball_pos = (x, y);
-            } else if val == 3 {
-                self.paddle_pos = (x, y);
-            }
-        }
-    }
-
-    fn process_output(
-        &mut self,
-        mut output: impl Iterator<Item = i64>,
-    ) -> Result<(), &'static str> {
-        while let Some(x) = output.next() {
-            let y = output.next().ok_or("invalid output")?;
-            let val = output.next().ok_or("invalid output")?;
-            self.process_triple(x, y, val);
-        }
-
-        Ok(())
-    }
-
-    fn count(&self, tile_type: i64) -> usize {
-        self.grid.values().filter(|t| **t == tile_type).count()
-    }
-}
+mod frames;
+mod game;
+mod player;
+mod replay;
+mod screen;
+
+use analysis::ScoreTable;
+use frames::{parse_palette, Format, FrameExporter};
+use game::Game;
+use player::{Lookahead, Player, Tracker};
+use replay::{draw_frame, Recording};
+use screen::{Screen, BLOCK, DEFAULT_PALETTE};
+use std::env;
+use std::fs;
 
 fn part1(program: emulator::Program) {
     let mut emu = emulator::Emulator::new(program);
@@ -75,52 +38,180 @@ fn part1(program: emulator::Program) {
         .process_output(output_buffer.into_iter())
         .expect("failed to parse screen");
 
-    let num_block_tile = screen.count(2);
+    let num_block_tile = screen.count(BLOCK);
     println!("Part 1: number of block tiles = {}", num_block_tile);
 }
 
-fn part2(program: emulator::Program) {
-    let mut screen = Screen::new();
-    let mut emu = emulator::Emulator::new(program);
-    emu.store(0, 2);
+#[derive(Default)]
+struct Options {
+    render: bool,
+    color: bool,
+    record_path: Option<String>,
+    frames: Option<FrameExporter>,
+}
 
-    let mut output_buffer = Vec::with_capacity(3);
+fn part2(program: emulator::Program, player: &mut dyn Player, options: &mut Options) {
+    let mut game = Game::new(program);
+    let mut recording = Recording::new();
 
     loop {
-        let paddle_cmp_ball = screen.paddle_pos.0.cmp(&screen.ball_pos.0);
+        for (x, y, val) in game.run_frame().expect("emulator failed") {
+            recording.tile(x, y, val);
+        }
+        if game.halted {
+            break;
+        }
 
-        let halted = emu
-            .step(
-                || {
-                    Ok(match paddle_cmp_ball {
-                        Ordering::Equal => 0,
-                        Ordering::Less => 1,
-                        Ordering::Greater => -1,
-                    })
-                },
-                |output| {
-                    output_buffer.push(output);
-                    if output_buffer.len() == 3 {
-                        screen.process_triple(output_buffer[0], output_buffer[1], output_buffer[2]);
-                        output_buffer.clear();
-                    }
-                    Ok(())
-                },
-            )
-            .expect("emulator failed");
+        // the game reads the joystick once it has drawn a frame
+        recording.end_frame();
+        if options.render {
+            draw_frame(&game.screen, options.color);
+        }
+        if let Some(frames) = &mut options.frames {
+            frames.frame(&game.screen).expect("failed to write frame");
+        }
 
-        if halted {
-            break;
+        let joystick = player.joystick(&game).expect("player failed");
+        game.send(joystick).expect("emulator failed");
+    }
+
+    if options.render {
+        draw_frame(&game.screen, options.color);
+    }
+    if let Some(frames) = &mut options.frames {
+        frames.frame(&game.screen).expect("failed to write frame");
+    }
+    if let Some(path) = &options.record_path {
+        fs::write(path, recording.to_string()).expect("failed to write recording");
+    }
+    println!("Part 2: score = {}", game.screen.score);
+}
+
+fn compare_players(program: emulator::Program) {
+    let players: Vec<Box<dyn Player>> = vec![Box::new(Tracker), Box::new(Lookahead::new())];
+    for mut player in players {
+        let mut game = Game::new(program.clone());
+        loop {
+            game.run_frame().expect("emulator failed");
+            if game.halted {
+                break;
+            }
+            let joystick = player.joystick(&game).expect("player failed");
+            game.send(joystick).expect("emulator failed");
+        }
+
+        let result = if game.screen.count(BLOCK) == 0 {
+            "won"
+        } else {
+            "lost"
         };
+        println!(
+            "{}: {} with score {}, {} inputs, {} instructions ({} more looking ahead)",
+            player.name(),
+            result,
+            game.screen.score,
+            game.inputs,
+            game.instructions(),
+            player.lookahead_instructions()
+        );
     }
+}
 
-    println!("Part 2: score = {}", screen.score);
+fn analyze_game(program: emulator::Program, player: &mut dyn Player) {
+    let table = ScoreTable::locate(program.clone()).expect("failed to locate score table");
+    println!(
+        "score at {}, score table at {} (multiplier {}, offset {}): final score {}",
+        table.score_address,
+        table.address,
+        table.multiplier,
+        table.offset,
+        table.final_score()
+    );
+
+    let stats = analysis::play(program, player, &table).expect("emulator failed");
+    print!("{}", stats);
 }
 
 fn main() {
+    let usage = "usage: day13 [--render] [--color] [--record <file>] [--replay <file>] \
+                 [--fps <n>] [--frames <dir> [--pbm] [--palette <colors>] [--scale <n>] \
+                 [--every <n>] [--score-frames]] [--player tracker|lookahead] \
+                 [--compare-players] [--analyze]";
+    let mut options = Options::default();
+    let mut replay_path = None;
+    let mut fps = None;
+    let mut frames_dir = None;
+    let mut format = Format::Ppm(DEFAULT_PALETTE);
+    let mut scale = 4;
+    let mut every = 1;
+    let mut score_frames = false;
+    let mut player: Box<dyn Player> = Box::new(Tracker);
+    let mut compare = false;
+    let mut analyze = false;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().expect(usage);
+        match arg.as_str() {
+            "--render" => options.render = true,
+            "--color" => options.color = true,
+            "--record" => options.record_path = Some(value()),
+            "--replay" => replay_path = Some(value()),
+            "--fps" => {
+                let rate = value().parse().ok().filter(|&fps| fps > 0);
+                fps = Some(rate.expect("invalid frame rate"))
+            }
+            "--frames" => frames_dir = Some(value()),
+            "--pbm" => format = Format::Pbm,
+            "--palette" => format = Format::Ppm(parse_palette(&value()).expect("invalid palette")),
+            "--scale" => scale = value().parse().expect("invalid scale"),
+            "--every" => every = value().parse().expect("invalid frame interval"),
+            "--score-frames" => score_frames = true,
+            "--player" => {
+                player = match value().as_str() {
+                    "tracker" => Box::new(Tracker),
+                    "lookahead" => Box::new(Lookahead::new()),
+                    _ => panic!("{}", usage),
+                }
+            }
+            "--compare-players" => compare = true,
+            "--analyze" => analyze = true,
+            _ => panic!("{}", usage),
+        }
+    }
+    if let Some(dir) = frames_dir {
+        let mut frames = FrameExporter::new(dir, format, scale).every(every);
+        if score_frames {
+            frames = frames.score_changes();
+        }
+        options.frames = Some(frames);
+    }
+
+    if let Some(path) = replay_path {
+        let recording: Recording = fs::read_to_string(path)
+            .expect("failed to read recording")
+            .parse()
+            .expect("failed to parse recording");
+        match &mut options.frames {
+            Some(frames) => recording.for_each_frame(|screen| {
+                frames.frame(screen).expect("failed to write frame");
+            }),
+            None => recording.play(fps, options.color),
+        }
+        return;
+    }
+
     let input = include_str!("input.txt");
     let program = emulator::Program::new(input).expect("failed to parse program");
 
+    if analyze {
+        analyze_game(program, player.as_mut());
+        return;
+    }
+    if compare {
+        compare_players(program);
+        return;
+    }
     part1(program.clone());
-    part2(program);
+    part2(program, player.as_mut(), &mut options);
 }
@@ -0,0 +1,134 @@
+// strategies for moving the paddle
+
+use crate::game::Game;
+use std::cmp::Ordering;
+
+pub trait Player {
+    fn name(&self) -> &'static str;
+    // returns the joystick position for the frame the game has just drawn
+    fn joystick(&mut self, game: &Game) -> Result<i64, &'static str>;
+    // number of instructions spent simulating ahead of the game
+    fn lookahead_instructions(&self) -> u64 {
+        0
+    }
+}
+
+fn toward(paddle_x: i64, target_x: i64) -> i64 {
+    match paddle_x.cmp(&target_x) {
+        Ordering::Equal => 0,
+        Ordering::Less => 1,
+        Ordering::Greater => -1,
+    }
+}
+
+// follows the ball's current column
+pub struct Tracker;
+
+impl Player for Tracker {
+    fn name(&self) -> &'static str {
+        "tracker"
+    }
+
+    fn joystick(&mut self, game: &Game) -> Result<i64, &'static str> {
+        Ok(toward(game.screen.paddle_pos.0, game.screen.ball_pos.0))
+    }
+}
+
+// predicts the column the ball lands in by running a copy of the game ahead, and waits there
+pub struct Lookahead {
+    target: Option<i64>,
+    instructions: u64,
+}
+
+impl Lookahead {
+    pub fn new() -> Self {
+        Lookahead {
+            target: None,
+            instructions: 0,
+        }
+    }
+
+    // returns the ball's column the next time it reaches the row above the paddle
+    fn predict(&mut self, game: &Game) -> Result<i64, &'static str> {
+        let landing_row = game.screen.paddle_pos.1 - 1;
+        let mut fork = game.clone();
+        let res = loop {
+            fork.send(0)?;
+            fork.run_frame()?;
+            if fork.halted || fork.screen.ball_pos.1 == landing_row {
+                break fork.screen.ball_pos.0;
+            }
+        };
+        self.instructions += fork.instructions() - game.instructions();
+        Ok(res)
+    }
+}
+
+impl Player for Lookahead {
+    fn name(&self) -> &'static str {
+        "lookahead"
+    }
+
+    fn joystick(&mut self, game: &Game) -> Result<i64, &'static str> {
+        let (ball_x, ball_y) = game.screen.ball_pos;
+        let (paddle_x, paddle_y) = game.screen.paddle_pos;
+        if ball_y == paddle_y - 1 {
+            // the ball is about to bounce, so predict again once it has
+            self.target = None;
+            return Ok(toward(paddle_x, ball_x));
+        }
+
+        let target = match self.target {
+            Some(target) => target,
+            None => {
+                let target = self.predict(game)?;
+                self.target = Some(target);
+                target
+            }
+        };
+        Ok(toward(paddle_x, target))
+    }
+
+    fn lookahead_instructions(&self) -> u64 {
+        self.instructions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Lookahead, Player};
+    use crate::emulator::Program;
+    use crate::game::Game;
+
+    // a paddle at (5, 4) and a ball that moves down and right one step each frame from (0, 0);
+    // the first instruction only touches scratch memory, as the game sets address 0
+    fn diagonal_game() -> Game {
+        let code = "1,33,33,33,\
+                    104,5,104,4,104,3,\
+                    4,30,4,31,104,4,\
+                    3,32,\
+                    1001,30,1,30,1001,31,1,31,\
+                    1105,1,10,\
+                    99,\
+                    0,0,0,0";
+        Game::new(Program::new(code).unwrap())
+    }
+
+    #[test]
+    fn test_predict() {
+        let mut game = diagonal_game();
+        game.run_frame().unwrap();
+        assert_eq!(game.screen.ball_pos, (0, 0));
+        assert_eq!(game.screen.paddle_pos, (5, 4));
+
+        // the ball reaches the row above the paddle at column 3
+        let mut player = Lookahead::new();
+        assert_eq!(player.predict(&game).unwrap(), 3);
+        assert!(player.lookahead_instructions() > 0);
+        // the game itself has not moved on
+        assert_eq!(game.screen.ball_pos, (0, 0));
+
+        assert_eq!(player.joystick(&game).unwrap(), -1);
+        assert_eq!(player.target, Some(3));
+    }
+}
@@ -0,0 +1,139 @@
+// record and replay the tile updates of a game
+//
+// A recording has one update per line, as `x y tile` (with x = -1 and y = 0 for the score), and
+// an empty line at the end of each frame, where the game reads the joystick.
+
+use crate::screen::Screen;
+use std::fmt::{Display, Error, Formatter};
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+
+enum Update {
+    Tile(i64, i64, i64),
+    EndFrame,
+}
+
+pub struct Recording {
+    updates: Vec<Update>,
+}
+
+impl Recording {
+    pub fn new() -> Self {
+        Recording {
+            updates: Vec::new(),
+        }
+    }
+
+    pub fn tile(&mut self, x: i64, y: i64, val: i64) {
+        self.updates.push(Update::Tile(x, y, val));
+    }
+
+    pub fn end_frame(&mut self) {
+        self.updates.push(Update::EndFrame);
+    }
+
+    // calls `on_frame` with the screen at the end of every frame, and once the game is over
+    pub fn for_each_frame(&self, mut on_frame: impl FnMut(&Screen)) {
+        let mut screen = Screen::new();
+        for update in &self.updates {
+            match *update {
+                Update::Tile(x, y, val) => screen.process_triple(x, y, val),
+                Update::EndFrame => on_frame(&screen),
+            }
+        }
+        on_frame(&screen);
+    }
+
+    // draws every frame, waiting between them if `fps` is given
+    pub fn play(&self, fps: Option<u32>, color: bool) {
+        self.for_each_frame(|screen| {
+            draw_frame(screen, color);
+            if let Some(fps) = fps {
+                thread::sleep(Duration::from_secs(1) / fps);
+            }
+        });
+    }
+}
+
+impl Display for Recording {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        for update in &self.updates {
+            match update {
+                Update::Tile(x, y, val) => writeln!(f, "{} {} {}", x, y, val)?,
+                Update::EndFrame => writeln!(f)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Recording {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let updates = s
+            .lines()
+            .map(|line| {
+                if line.trim().is_empty() {
+                    return Ok(Update::EndFrame);
+                }
+                let vals = line
+                    .split_whitespace()
+                    .map(|val| val.parse().map_err(|_| "invalid tile update"))
+                    .collect::<Result<Vec<i64>, &'static str>>()?;
+                match vals[..] {
+                    [x, y, val] => Ok(Update::Tile(x, y, val)),
+                    _ => Err("invalid tile update"),
+                }
+            })
+            .collect::<Result<Vec<Update>, &'static str>>()?;
+        Ok(Recording { updates })
+    }
+}
+
+// clears the terminal and draws the screen
+pub fn draw_frame(screen: &Screen, color: bool) {
+    print!("\x1b[H\x1b[2J{}", screen.render(color));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Recording;
+
+    #[test]
+    fn test_round_trip() {
+        let mut recording = Recording::new();
+        recording.tile(1, 2, 3);
+        recording.tile(-1, 0, 40);
+        recording.end_frame();
+        recording.tile(2, 2, 4);
+        recording.end_frame();
+        let text = recording.to_string();
+        assert_eq!(text, "1 2 3\n-1 0 40\n\n2 2 4\n\n");
+
+        let parsed: Recording = text.parse().unwrap();
+        assert_eq!(parsed.to_string(), text);
+
+        // one call per end of frame, and one for the end of the game
+        let mut frames = Vec::new();
+        parsed.for_each_frame(|screen| frames.push((screen.score, screen.ball_pos)));
+        assert_eq!(frames, vec![(40, (0, 0)), (40, (2, 2)), (40, (2, 2))]);
+    }
+
+    #[test]
+    fn test_bad_lines() {
+        // blank lines, even with spaces, end a frame
+        let recording: Recording = "1 2 3\n  \n".parse().unwrap();
+        assert_eq!(recording.to_string(), "1 2 3\n\n");
+
+        for text in ["1 2\n", "1 2 3 4\n", "1 2 x\n", "1 2 3\n\n1.5 2 3\n"].iter() {
+            assert_eq!(
+                text.parse::<Recording>().err(),
+                Some("invalid tile update"),
+                "{:?}",
+                text
+            );
+        }
+    }
+}
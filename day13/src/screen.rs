@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+
+pub const EMPTY: i64 = 0;
+pub const WALL: i64 = 1;
+pub const BLOCK: i64 = 2;
+pub const PADDLE: i64 = 3;
+pub const BALL: i64 = 4;
+
+// RGB color of each tile type, indexed by tile
+pub type Palette = [[u8; 3]; 5];
+
+pub const DEFAULT_PALETTE: Palette = [
+    [0, 0, 0],       // empty
+    [128, 128, 128], // wall
+    [230, 160, 40],  // block
+    [60, 180, 230],  // paddle
+    [240, 60, 60],   // ball
+];
+
+#[derive(Clone)]
+pub struct Screen {
+    pub grid: HashMap<(i64, i64), i64>,
+    pub score: i64,
+
+    pub ball_pos: (i64, i64),
+    pub paddle_pos: (i64, i64),
+}
+
+impl Screen {
+    pub fn new() -> Self {
+        Screen {
+            grid: HashMap::new(),
+            score: 0,
+            ball_pos: (0, 0),
+            paddle_pos: (0, 0),
+        }
+    }
+
+    pub fn process_triple(&mut self, x: i64, y: i64, val: i64) {
+        if x == -1 && y == 0 {
+            self.score = val;
+        } else {
+            self.grid.insert((x, y), val);
+            if val == BALL {
+                self.ball_pos = (x, y);
+            } else if val == PADDLE {
+                self.paddle_pos = (x, y);
+            }
+        }
+    }
+
+    pub fn process_output(
+        &mut self,
+        mut output: impl Iterator<Item = i64>,
+    ) -> Result<(), &'static str> {
+        while let Some(x) = output.next() {
+            let y = output.next().ok_or("invalid output")?;
+            let val = output.next().ok_or("invalid output")?;
+            self.process_triple(x, y, val);
+        }
+
+        Ok(())
+    }
+
+    pub fn count(&self, tile_type: i64) -> usize {
+        self.grid.values().filter(|t| **t == tile_type).count()
+    }
+
+    // returns (width, height)
+    pub fn size(&self) -> (i64, i64) {
+        let width = self.grid.keys().map(|&(x, _)| x + 1).max().unwrap_or(0);
+        let height = self.grid.keys().map(|&(_, y)| y + 1).max().unwrap_or(0);
+        (width, height)
+    }
+
+    // draws the game as text, optionally with ANSI colors
+    pub fn render(&self, color: bool) -> String {
+        let (width, height) = self.size();
+        let mut res = format!("Score: {}\n", self.score);
+        for y in 0..height {
+            for x in 0..width {
+                let (c, ansi) = match self.tile(x, y) {
+                    WALL => ('#', "37"),
+                    BLOCK => ('=', "33"),
+                    PADDLE => ('-', "36"),
+                    BALL => ('o', "31"),
+                    _ => (' ', ""),
+                };
+                if color && !ansi.is_empty() {
+                    res.push_str(&format!("\x1b[{}m{}\x1b[0m", ansi, c));
+                } else {
+                    res.push(c);
+                }
+            }
+            res.push('\n');
+        }
+        res
+    }
+
+    pub fn tile(&self, x: i64, y: i64) -> i64 {
+        self.grid.get(&(x, y)).cloned().unwrap_or(EMPTY)
+    }
+
+    // binary PPM image, with each tile drawn as a `scale` by `scale` square
+    pub fn to_ppm(&self, palette: &Palette, scale: usize) -> Vec<u8> {
+        let (width, height) = self.size();
+        let (width, height) = (width as usize, height as usize);
+        let mut res = format!("P6\n{} {}\n255\n", width * scale, height * scale).into_bytes();
+        for y in 0..height * scale {
+            for x in 0..width * scale {
+                let tile = self.tile((x / scale) as i64, (y / scale) as i64);
+                res.extend_from_slice(&palette[tile as usize % palette.len()]);
+            }
+        }
+        res
+    }
+
+    // binary PBM image, with every non-empty tile set
+    pub fn to_pbm(&self, scale: usize) -> Vec<u8> {
+        let (width, height) = self.size();
+        let (width, height) = (width as usize, height as usize);
+        let mut res = format!("P4\n{} {}\n", width * scale, height * scale).into_bytes();
+        let row_bytes = (width * scale).div_ceil(8);
+        for y in 0..height * scale {
+            let mut row = vec![0u8; row_bytes];
+            for x in 0..width * scale {
+                if self.tile((x / scale) as i64, (y / scale) as i64) != EMPTY {
+                    row[x / 8] |= 0x80 >> (x % 8);
+                }
+            }
+            res.extend_from_slice(&row);
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Screen, BALL, BLOCK, DEFAULT_PALETTE, WALL};
+
+    // 10 tiles wide, which doesn't fill whole bytes of a PBM row
+    fn screen() -> Screen {
+        let mut screen = Screen::new();
+        screen.process_triple(0, 0, WALL);
+        screen.process_triple(9, 0, BLOCK);
+        screen.process_triple(8, 1, BALL);
+        screen
+    }
+
+    #[test]
+    fn test_pbm() {
+        let header = b"P4\n10 2\n";
+        let pbm = screen().to_pbm(1);
+        assert_eq!(&pbm[..header.len()], header);
+        assert_eq!(&pbm[header.len()..], &[0x80, 0x40, 0x00, 0x80]);
+
+        let header = b"P4\n20 4\n";
+        let pbm = screen().to_pbm(2);
+        assert_eq!(&pbm[..header.len()], header);
+        assert_eq!(
+            &pbm[header.len()..],
+            &[0xc0, 0x00, 0x30, 0xc0, 0x00, 0x30, 0x00, 0x00, 0xc0, 0x00, 0x00, 0xc0]
+        );
+    }
+
+    #[test]
+    fn test_ppm() {
+        let header = b"P6\n30 6\n255\n";
+        let ppm = screen().to_ppm(&DEFAULT_PALETTE, 3);
+        assert_eq!(&ppm[..header.len()], header);
+        let pixels = &ppm[header.len()..];
+        assert_eq!(pixels.len(), 30 * 6 * 3);
+
+        let pixel = |x: usize, y: usize| &pixels[(y * 30 + x) * 3..(y * 30 + x) * 3 + 3];
+        assert_eq!(pixel(0, 0), DEFAULT_PALETTE[WALL as usize]);
+        assert_eq!(pixel(2, 2), DEFAULT_PALETTE[WALL as usize]);
+        assert_eq!(pixel(3, 0), DEFAULT_PALETTE[0]);
+        assert_eq!(pixel(29, 2), DEFAULT_PALETTE[BLOCK as usize]);
+        assert_eq!(pixel(24, 3), DEFAULT_PALETTE[BALL as usize]);
+    }
+}
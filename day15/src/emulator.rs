@@ -111,7 +111,7 @@ impl Emulator {
         let address = address as usize;
         if address >= self.memory.len() {
             self.memory
-                .extend(iter::repeat(0).take(address - self.memory.len() + 1))
+                .extend(iter::repeat_n(0, address - self.memory.len() + 1))
         }
         self.memory[address] = value;
     }
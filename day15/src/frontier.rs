@@ -0,0 +1,163 @@
+// iterative exploration that always heads for the nearest unexplored cell
+
+use crate::{Maze, Movement, Robot, Status, Tile, DIRECTIONS};
+use std::collections::{HashMap, VecDeque};
+
+// explores the maze from the robot's position at (0, 0), returning the number of moves used
+pub fn explore_frontier<T: Robot>(robot: &mut T, maze: &mut Maze) -> Result<usize, &'static str> {
+    let mut pos = (0, 0);
+    let mut moves = 0;
+    maze.0.entry(pos).or_insert(Tile::Empty);
+
+    while let Some(path) = path_to_frontier(maze, pos) {
+        let (last, known) = path.split_last().unwrap();
+        for dir in known {
+            let (dx, dy) = dir.to_delta();
+            if robot.send_move(*dir).map_err(|_| "robot failed")? == Status::HitWall {
+                return Err("robot hit a wall in the explored maze");
+            }
+            pos = (pos.0 + dx, pos.1 + dy);
+        }
+
+        let (dx, dy) = last.to_delta();
+        let new_pos = (pos.0 + dx, pos.1 + dy);
+        let tile = match robot.send_move(*last).map_err(|_| "robot failed")? {
+            Status::HitWall => Tile::Wall,
+            Status::Moved => Tile::Empty,
+            Status::MovedAndFinished => Tile::Oxygen,
+        };
+        if !matches!(tile, Tile::Wall) {
+            pos = new_pos;
+        }
+        maze.0.insert(new_pos, tile);
+        moves += path.len();
+    }
+
+    Ok(moves)
+}
+
+// breadth first search over the explored open cells for the nearest unexplored one, returning the
+// moves that lead there
+fn path_to_frontier(maze: &Maze, start: (i64, i64)) -> Option<Vec<Movement>> {
+    let mut came_from: HashMap<(i64, i64), Option<Movement>> = HashMap::new();
+    let mut queue = VecDeque::new();
+    came_from.insert(start, None);
+    queue.push_back(start);
+
+    while let Some((x, y)) = queue.pop_front() {
+        for dir in DIRECTIONS.iter() {
+            let (dx, dy) = dir.to_delta();
+            let next = (x + dx, y + dy);
+            if came_from.contains_key(&next) {
+                continue;
+            }
+            came_from.insert(next, Some(*dir));
+            match maze.0.get(&next) {
+                None => return Some(backtrack(&came_from, next)),
+                Some(Tile::Wall) => {}
+                Some(_) => queue.push_back(next),
+            }
+        }
+    }
+
+    None
+}
+
+fn backtrack(
+    came_from: &HashMap<(i64, i64), Option<Movement>>,
+    mut pos: (i64, i64),
+) -> Vec<Movement> {
+    let mut path = Vec::new();
+    while let Some(dir) = came_from[&pos] {
+        let (dx, dy) = dir.to_delta();
+        pos = (pos.0 - dx, pos.1 - dy);
+        path.push(dir);
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::explore_frontier;
+    use crate::map_robot::MapRobot;
+    use crate::{explore_map, Maze, Robot};
+
+    // explores with both strategies, checking they agree, and returns the moves each used
+    fn compare(maze: &str) -> (usize, usize) {
+        let mut robot = MapRobot::new(maze).unwrap();
+        let mut dfs = Maze::new();
+        explore_map(&mut robot, (0, 0), &mut dfs).unwrap();
+        let dfs_moves = robot.moves();
+
+        let mut robot = MapRobot::new(maze).unwrap();
+        let mut frontier = Maze::new();
+        let moves = explore_frontier(&mut robot, &mut frontier).unwrap();
+        assert_eq!(moves, robot.moves());
+
+        assert_eq!(frontier.0.len(), dfs.0.len());
+        let (oxygen_pos, steps) = frontier.find_oxygen().unwrap();
+        assert_eq!((oxygen_pos, steps), dfs.find_oxygen().unwrap());
+        assert_eq!(
+            frontier.time_to_fill_oxygen_from(oxygen_pos),
+            dfs.time_to_fill_oxygen_from(oxygen_pos)
+        );
+        (dfs_moves, moves)
+    }
+
+    #[test]
+    fn test_corridor() {
+        // no walking back once the far end is found
+        let (dfs, frontier) = compare("#######\n#D...O#\n#######");
+        assert_eq!(dfs, 2 * 5 + 12);
+        assert_eq!(frontier, 4 + 12);
+    }
+
+    #[test]
+    fn test_loops() {
+        let maze = "#########\n\
+                    #.......#\n\
+                    #.#####.#\n\
+                    #.#####.#\n\
+                    #...D..O#\n\
+                    #########";
+        let (dfs, frontier) = compare(maze);
+        assert!(frontier < dfs);
+
+        let maze = "######\n\
+                    #D...#\n\
+                    #....#\n\
+                    #...O#\n\
+                    ######";
+        let (dfs, frontier) = compare(maze);
+        assert!(frontier < dfs);
+    }
+
+    #[test]
+    fn test_large_room() {
+        // big enough that the search would recurse thousands of levels deep
+        let width = 80;
+        let mut maze = format!("{}\n", "#".repeat(width + 2));
+        for y in 0..width {
+            let row: String = (0..width)
+                .map(|x| match (x, y) {
+                    (0, 0) => 'D',
+                    (x, y) if x == width - 1 && y == width - 1 => 'O',
+                    _ => '.',
+                })
+                .collect();
+            maze.push_str(&format!("#{}#\n", row));
+        }
+        maze.push_str(&"#".repeat(width + 2));
+
+        let mut robot = MapRobot::new(&maze).unwrap();
+        let mut explored = Maze::new();
+        explore_frontier(&mut robot, &mut explored).unwrap();
+        let (oxygen_pos, steps) = explored.find_oxygen().unwrap();
+        assert_eq!(steps, 2 * (width - 1));
+        assert_eq!(
+            explored.time_to_fill_oxygen_from(oxygen_pos).unwrap(),
+            2 * (width - 1)
+        );
+    }
+}
@@ -1,9 +1,15 @@
 mod emulator;
+mod frontier;
+mod map_robot;
+mod render;
 
+use frontier::explore_frontier;
+use map_robot::MapRobot;
 use std::{
-    cmp::max,
     collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
     convert::{TryFrom, TryInto},
+    env, fs,
+    path::Path,
 };
 
 // interface for robot in maze
@@ -66,6 +72,8 @@ impl TryFrom<i64> for Status {
 trait Robot {
     type Error;
     fn send_move(&mut self, movement: Movement) -> Result<Status, Self::Error>;
+    // number of move commands sent, including those into walls
+    fn moves(&self) -> usize;
 }
 
 // maze exploration
@@ -147,13 +155,14 @@ impl Maze {
         }
     }
 
-    fn time_to_fill_oxygen_from(&self, pos: (i64, i64)) -> Result<usize, &'static str> {
+    // returns the cells the oxygen newly reaches in each minute, starting with `pos` itself
+    fn oxygen_fronts(&self, pos: (i64, i64)) -> Result<Vec<Vec<(i64, i64)>>, &'static str> {
         // exhaustive breadth first search from oxygen source
         let mut seen: HashSet<(i64, i64)> = HashSet::new();
         let mut queue: VecDeque<((i64, i64), usize)> = VecDeque::new();
         queue.push_back((pos, 0));
 
-        let mut max_depth = 0;
+        let mut fronts: Vec<Vec<(i64, i64)>> = Vec::new();
 
         while !queue.is_empty() {
             let ((x, y), steps) = queue.pop_front().unwrap();
@@ -167,7 +176,10 @@ impl Maze {
                 continue;
             }
 
-            max_depth = max(max_depth, steps);
+            if fronts.len() <= steps {
+                fronts.push(Vec::new());
+            }
+            fronts[steps].push((x, y));
 
             for dir in DIRECTIONS.iter() {
                 let (dx, dy) = dir.to_delta();
@@ -175,7 +187,11 @@ impl Maze {
             }
         }
 
-        Ok(max_depth)
+        Ok(fronts)
+    }
+
+    fn time_to_fill_oxygen_from(&self, pos: (i64, i64)) -> Result<usize, &'static str> {
+        Ok(self.oxygen_fronts(pos)?.len().saturating_sub(1))
     }
 }
 
@@ -183,12 +199,14 @@ impl Maze {
 
 struct IntcodeRobot {
     emu: emulator::Emulator,
+    moves: usize,
 }
 
 impl IntcodeRobot {
     fn new(program: emulator::Program) -> Self {
         Self {
             emu: emulator::Emulator::new(program),
+            moves: 0,
         }
     }
 }
@@ -200,6 +218,7 @@ impl Robot for IntcodeRobot {
         let mut result = None;
 
         let input = movement as i64;
+        self.moves += 1;
         while result.is_none() {
             self.emu.step(
                 || Ok(input),
@@ -210,23 +229,103 @@ impl Robot for IntcodeRobot {
             )?;
         }
 
-        Ok(result.unwrap().try_into()?)
+        result.unwrap().try_into()
+    }
+
+    fn moves(&self) -> usize {
+        self.moves
     }
 }
 
-fn main() {
-    let input = include_str!("input.txt");
-    let program = emulator::Program::new(input).expect("failed to parse program");
+#[derive(Copy, Clone)]
+enum Explorer {
+    DepthFirst,
+    Frontier,
+}
 
-    let mut robot = IntcodeRobot::new(program);
+struct Options {
+    explorer: Explorer,
+    render: bool,
+    flood: bool,
+    flood_dir: Option<String>,
+    scale: usize,
+}
+
+fn solve<T: Robot>(robot: &mut T, options: &Options) {
     let mut maze = Maze::new();
 
-    explore_map(&mut robot, (0, 0), &mut maze).expect("failed to explore maze");
+    match options.explorer {
+        Explorer::DepthFirst => explore_map(robot, (0, 0), &mut maze),
+        Explorer::Frontier => explore_frontier(robot, &mut maze).map(|_| ()),
+    }
+    .expect("failed to explore maze");
+    println!("robot moves = {}", robot.moves());
+    if options.render {
+        let path = maze.path_to_oxygen().expect("failed to find oxygen");
+        print!("{}", maze.render(&render::path_marks(&path)));
+    }
     let (oxygen_pos, length) = maze.find_oxygen().expect("failed to find oxygen");
     println!("Part 1: steps to solve maze = {}", length);
 
+    if options.flood {
+        render::print_flood(&maze, oxygen_pos).expect("failed to flood maze");
+    }
+    if let Some(dir) = &options.flood_dir {
+        render::export_flood(&maze, oxygen_pos, Path::new(dir), options.scale)
+            .expect("failed to write flood frames");
+    }
     let time_to_fill_oxygen = maze
         .time_to_fill_oxygen_from(oxygen_pos)
         .expect("failed to find time for oxygen fill");
     println!("Part 2: time to fill oxygen = {}", time_to_fill_oxygen);
 }
+
+fn main() {
+    let usage = "usage: day15 [--maze <file> [--budget <moves>]] [--explorer dfs|frontier] \
+                 [--render] [--flood] [--flood-frames <dir> [--scale <n>]]";
+    let mut maze_path = None;
+    let mut budget = None;
+    let mut options = Options {
+        explorer: Explorer::Frontier,
+        render: false,
+        flood: false,
+        flood_dir: None,
+        scale: 8,
+    };
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().expect(usage);
+        match arg.as_str() {
+            "--maze" => maze_path = Some(value()),
+            "--budget" => budget = Some(value().parse().expect("invalid move budget")),
+            "--explorer" => {
+                options.explorer = match value().as_str() {
+                    "dfs" => Explorer::DepthFirst,
+                    "frontier" => Explorer::Frontier,
+                    _ => panic!("{}", usage),
+                }
+            }
+            "--render" => options.render = true,
+            "--flood" => options.flood = true,
+            "--flood-frames" => options.flood_dir = Some(value()),
+            "--scale" => options.scale = value().parse().expect("invalid scale"),
+            _ => panic!("{}", usage),
+        }
+    }
+
+    if let Some(path) = maze_path {
+        // solve a hand-drawn maze instead of the puzzle input
+        let maze = fs::read_to_string(path).expect("failed to read maze");
+        let mut robot = MapRobot::new(&maze).expect("failed to parse maze");
+        if let Some(budget) = budget {
+            robot = robot.with_budget(budget);
+        }
+        solve(&mut robot, &options);
+        return;
+    }
+
+    let input = include_str!("input.txt");
+    let program = emulator::Program::new(input).expect("failed to parse program");
+    solve(&mut IntcodeRobot::new(program), &options);
+}
@@ -0,0 +1,193 @@
+// robot that moves through a hand-drawn maze instead of running the droid program
+//
+// Mazes are drawn with '#' for walls, '.' for open cells, 'O' for the oxygen system and 'D' for
+// the droid's starting cell. Anything outside the drawing is wall.
+
+use crate::{Movement, Robot, Status};
+use std::collections::HashMap;
+
+#[derive(Eq, PartialEq, Copy, Clone)]
+enum Cell {
+    Open,
+    Oxygen,
+}
+
+pub struct MapRobot {
+    cells: HashMap<(i64, i64), Cell>,
+    pos: (i64, i64),
+    moves: usize,
+    budget: Option<usize>,
+}
+
+impl MapRobot {
+    // parses a maze, placing the droid's starting cell at (0, 0) with north up
+    pub fn new(maze: &str) -> Result<Self, &'static str> {
+        let mut cells = HashMap::new();
+        let mut start = None;
+        for (row, line) in maze.lines().enumerate() {
+            for (col, c) in line.chars().enumerate() {
+                let pos = (col as i64, -(row as i64));
+                let cell = match c {
+                    '#' | ' ' => continue,
+                    '.' => Cell::Open,
+                    'O' => Cell::Oxygen,
+                    'D' => {
+                        if start.replace(pos).is_some() {
+                            return Err("more than one droid");
+                        }
+                        Cell::Open
+                    }
+                    _ => return Err("invalid maze character"),
+                };
+                cells.insert(pos, cell);
+            }
+        }
+
+        let (sx, sy) = start.ok_or("no droid in maze")?;
+        let cells = cells
+            .into_iter()
+            .map(|((x, y), cell)| ((x - sx, y - sy), cell))
+            .collect();
+        Ok(MapRobot {
+            cells,
+            pos: (0, 0),
+            moves: 0,
+            budget: None,
+        })
+    }
+
+    // fails any move after the first `budget`
+    pub fn with_budget(mut self, budget: usize) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+}
+
+impl Robot for MapRobot {
+    type Error = &'static str;
+
+    fn send_move(&mut self, movement: Movement) -> Result<Status, Self::Error> {
+        if self.budget.is_some_and(|budget| self.moves >= budget) {
+            return Err("move budget exhausted");
+        }
+        self.moves += 1;
+
+        let (dx, dy) = movement.to_delta();
+        let new_pos = (self.pos.0 + dx, self.pos.1 + dy);
+        Ok(match self.cells.get(&new_pos) {
+            None => Status::HitWall,
+            Some(cell) => {
+                self.pos = new_pos;
+                match cell {
+                    Cell::Open => Status::Moved,
+                    Cell::Oxygen => Status::MovedAndFinished,
+                }
+            }
+        })
+    }
+
+    fn moves(&self) -> usize {
+        self.moves
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MapRobot;
+    use crate::{explore_map, Maze, Movement, Robot, Status};
+
+    fn solve(maze: &str) -> (usize, usize) {
+        let mut robot = MapRobot::new(maze).unwrap();
+        let mut maze = Maze::new();
+        explore_map(&mut robot, (0, 0), &mut maze).unwrap();
+        let (oxygen_pos, steps) = maze.find_oxygen().unwrap();
+        (steps, maze.time_to_fill_oxygen_from(oxygen_pos).unwrap())
+    }
+
+    #[test]
+    fn test_moves() {
+        let mut robot = MapRobot::new("#####\n#D.O#\n#####").unwrap();
+        assert!(robot.send_move(Movement::North).unwrap() == Status::HitWall);
+        assert!(robot.send_move(Movement::East).unwrap() == Status::Moved);
+        assert!(robot.send_move(Movement::East).unwrap() == Status::MovedAndFinished);
+        assert!(robot.send_move(Movement::East).unwrap() == Status::HitWall);
+        assert_eq!(robot.moves(), 4);
+    }
+
+    #[test]
+    fn test_budget() {
+        let mut robot = MapRobot::new("D.O").unwrap().with_budget(1);
+        assert!(robot.send_move(Movement::East).is_ok());
+        assert!(robot.send_move(Movement::East).is_err());
+    }
+
+    #[test]
+    fn test_corridor() {
+        assert_eq!(solve("#######\n#D...O#\n#######"), (4, 4));
+    }
+
+    #[test]
+    fn test_puzzle_example() {
+        // the oxygen example from the puzzle, with the droid added
+        let maze = "######\n\
+                    #D.###\n\
+                    #.#..#\n\
+                    #.O.##\n\
+                    ######";
+        assert_eq!(solve(maze), (3, 4));
+    }
+
+    #[test]
+    fn test_loops() {
+        // two routes around a pillar, the shorter one to the east
+        let maze = "#########\n\
+                    #.......#\n\
+                    #.#####.#\n\
+                    #.#####.#\n\
+                    #...D..O#\n\
+                    #########";
+        assert_eq!(solve(maze), (3, 9));
+
+        // open room, where every cell can be reached several ways
+        let maze = "######\n\
+                    #D...#\n\
+                    #....#\n\
+                    #...O#\n\
+                    ######";
+        assert_eq!(solve(maze), (5, 5));
+    }
+
+    #[test]
+    fn test_explore_visits_every_cell() {
+        let maze = "#######\n\
+                    #D#...#\n\
+                    #.#.#.#\n\
+                    #...#O#\n\
+                    #######";
+        let mut robot = MapRobot::new(maze).unwrap();
+        let mut explored = Maze::new();
+        explore_map(&mut robot, (0, 0), &mut explored).unwrap();
+        assert_eq!(explored.0.len(), 11 + 18);
+        assert_eq!(explored.find_oxygen().unwrap(), ((4, -2), 10));
+
+        // every open cell is entered and left once, the start included since the search only
+        // marks it when coming back, and every wall is bumped into once
+        assert_eq!(robot.moves(), 2 * 11 + 18);
+    }
+
+    #[test]
+    fn test_explore_out_of_budget() {
+        let mut robot = MapRobot::new("#####\n#D..O#\n#####")
+            .unwrap()
+            .with_budget(3);
+        let mut maze = Maze::new();
+        assert!(explore_map(&mut robot, (0, 0), &mut maze).is_err());
+    }
+
+    #[test]
+    fn test_invalid_mazes() {
+        assert!(MapRobot::new("#..O#").is_err());
+        assert!(MapRobot::new("#D.D#").is_err());
+        assert!(MapRobot::new("#D.x#").is_err());
+    }
+}
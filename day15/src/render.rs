@@ -0,0 +1,182 @@
+// draw the explored maze, as text or as images of the oxygen spreading
+
+use crate::{Maze, Tile, DIRECTIONS};
+use std::collections::{hash_map::Entry, HashMap, VecDeque};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// what to draw over the maze
+#[derive(Eq, PartialEq, Copy, Clone)]
+pub enum Mark {
+    Path,
+    Filled,
+}
+
+pub type Marks = HashMap<(i64, i64), Mark>;
+
+impl Maze {
+    // returns the cells on a shortest path from the start to the oxygen system, both included
+    pub fn path_to_oxygen(&self) -> Result<Vec<(i64, i64)>, &'static str> {
+        // breadth first search, remembering where each cell was reached from
+        let mut came_from: HashMap<(i64, i64), (i64, i64)> = HashMap::new();
+        let mut queue = VecDeque::new();
+        came_from.insert((0, 0), (0, 0));
+        queue.push_back((0, 0));
+
+        while let Some((x, y)) = queue.pop_front() {
+            match self.0.get(&(x, y)).ok_or("unbounded map")? {
+                Tile::Oxygen => {
+                    let mut path = vec![(x, y)];
+                    let mut pos = (x, y);
+                    while pos != (0, 0) {
+                        pos = came_from[&pos];
+                        path.push(pos);
+                    }
+                    path.reverse();
+                    return Ok(path);
+                }
+                Tile::Wall => continue,
+                Tile::Empty => {}
+            }
+
+            for dir in DIRECTIONS.iter() {
+                let (dx, dy) = dir.to_delta();
+                let next = (x + dx, y + dy);
+                if let Entry::Vacant(e) = came_from.entry(next) {
+                    e.insert((x, y));
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        Err("no oxygen system in maze")
+    }
+
+    // returns ((min x, max y), (width, height)) of the explored cells
+    fn bounds(&self) -> ((i64, i64), (i64, i64)) {
+        let min_x = self.0.keys().map(|&(x, _)| x).min().unwrap_or(0);
+        let max_x = self.0.keys().map(|&(x, _)| x).max().unwrap_or(0);
+        let min_y = self.0.keys().map(|&(_, y)| y).min().unwrap_or(0);
+        let max_y = self.0.keys().map(|&(_, y)| y).max().unwrap_or(0);
+        ((min_x, max_y), (max_x - min_x + 1, max_y - min_y + 1))
+    }
+
+    // draws the maze with north up, using '#' for walls, 'D' for the start, 'O' for the oxygen
+    // system, '*' for path marks and '~' for filled marks
+    pub fn render(&self, marks: &Marks) -> String {
+        let ((left, top), (width, height)) = self.bounds();
+        let mut res = String::new();
+        for y in (top - height + 1..=top).rev() {
+            for x in left..left + width {
+                let c = match (self.0.get(&(x, y)), marks.get(&(x, y))) {
+                    (None, _) => ' ',
+                    (Some(Tile::Wall), _) => '#',
+                    (Some(Tile::Oxygen), _) => 'O',
+                    _ if (x, y) == (0, 0) => 'D',
+                    (Some(Tile::Empty), Some(Mark::Path)) => '*',
+                    (Some(Tile::Empty), Some(Mark::Filled)) => '~',
+                    (Some(Tile::Empty), None) => '.',
+                };
+                res.push(c);
+            }
+            res.push('\n');
+        }
+        res
+    }
+
+    // binary PPM image of the maze, with each cell drawn as a `scale` by `scale` square
+    pub fn to_ppm(&self, marks: &Marks, scale: usize) -> Vec<u8> {
+        let ((left, top), (width, height)) = self.bounds();
+        let (width, height) = (width as usize, height as usize);
+        let mut res = format!("P6\n{} {}\n255\n", width * scale, height * scale).into_bytes();
+        for row in 0..height * scale {
+            for col in 0..width * scale {
+                let pos = (left + (col / scale) as i64, top - (row / scale) as i64);
+                let color = match (self.0.get(&pos), marks.get(&pos)) {
+                    (None, _) => [0, 0, 0],
+                    (Some(Tile::Wall), _) => [128, 128, 128],
+                    (Some(Tile::Oxygen), _) => [240, 60, 60],
+                    _ if pos == (0, 0) => [60, 200, 60],
+                    (Some(Tile::Empty), Some(Mark::Path)) => [230, 160, 40],
+                    (Some(Tile::Empty), Some(Mark::Filled)) => [60, 180, 230],
+                    (Some(Tile::Empty), None) => [240, 240, 240],
+                };
+                res.extend_from_slice(&color);
+            }
+        }
+        res
+    }
+
+    // the maze at the end of each minute of the oxygen spreading from `pos`
+    pub fn flood_frames(&self, pos: (i64, i64)) -> Result<Vec<Marks>, &'static str> {
+        let mut filled = HashMap::new();
+        let mut frames = Vec::new();
+        for front in self.oxygen_fronts(pos)? {
+            filled.extend(front.into_iter().map(|pos| (pos, Mark::Filled)));
+            frames.push(filled.clone());
+        }
+        Ok(frames)
+    }
+}
+
+pub fn path_marks(path: &[(i64, i64)]) -> Marks {
+    path.iter().map(|&pos| (pos, Mark::Path)).collect()
+}
+
+// prints every minute of the oxygen spreading
+pub fn print_flood(maze: &Maze, pos: (i64, i64)) -> Result<(), &'static str> {
+    for (minute, marks) in maze.flood_frames(pos)?.iter().enumerate() {
+        println!("Minute {}:\n{}", minute, maze.render(marks));
+    }
+    Ok(())
+}
+
+// writes every minute of the oxygen spreading to `dir` as minute_000.ppm, minute_001.ppm, ...
+pub fn export_flood(maze: &Maze, pos: (i64, i64), dir: &Path, scale: usize) -> io::Result<()> {
+    let frames = maze
+        .flood_frames(pos)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    fs::create_dir_all(dir)?;
+    for (minute, marks) in frames.iter().enumerate() {
+        let path = dir.join(format!("minute_{:03}.ppm", minute));
+        fs::write(path, maze.to_ppm(marks, scale))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::path_marks;
+    use crate::frontier::explore_frontier;
+    use crate::map_robot::MapRobot;
+    use crate::Maze;
+
+    #[test]
+    fn test_render_path() {
+        let drawn = "#######\n\
+                     #D#...#\n\
+                     #.#.#.#\n\
+                     #...#O#\n\
+                     #######\n";
+        let mut robot = MapRobot::new(drawn).unwrap();
+        let mut maze = Maze::new();
+        explore_frontier(&mut robot, &mut maze).unwrap();
+
+        let path = maze.path_to_oxygen().unwrap();
+        assert_eq!(path.len(), 11);
+        // corners and walls the robot never bumped into stay blank
+        let expected = concat!(
+            " # ### \n",
+            "#D#***#\n",
+            "#*#*#*#\n",
+            "#***#O#\n",
+            " ### # \n",
+        );
+        assert_eq!(maze.render(&path_marks(&path)), expected);
+
+        let frames = maze.flood_frames(*path.last().unwrap()).unwrap();
+        assert_eq!(frames.len(), 11);
+        assert_eq!(maze.render(&frames[10]).matches('~').count(), 9);
+    }
+}
@@ -0,0 +1,299 @@
+// structure of the scaffold: special cells, straight segments and the graph between junctions
+
+use crate::{Direction, Image};
+use std::collections::HashMap;
+use std::fmt::{Display, Error, Formatter};
+
+// (row, col)
+pub type Pos = (usize, usize);
+
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Up,
+    Direction::Right,
+    Direction::Down,
+    Direction::Left,
+];
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Feature {
+    // scaffold on all four sides
+    Intersection,
+    // scaffold on three sides
+    Junction,
+    DeadEnd,
+    Corner,
+    Straight,
+    Isolated,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Orientation {
+    Horizontal,
+    Vertical,
+}
+
+// a maximal straight run of scaffold, from its top or left end
+#[derive(Debug, PartialEq)]
+pub struct Segment {
+    pub start: Pos,
+    pub end: Pos,
+    pub orientation: Orientation,
+    // number of steps from one end to the other
+    pub length: usize,
+}
+
+// scaffold followed from one node to another, through any corners
+pub struct Edge {
+    pub from: usize,
+    pub to: usize,
+    // the direction and number of steps of each straight stretch, starting from `from`
+    pub legs: Vec<(Direction, usize)>,
+}
+
+impl Edge {
+    pub fn steps(&self) -> usize {
+        self.legs.iter().map(|&(_, steps)| steps).sum()
+    }
+}
+
+// nodes are the cells where the scaffold does not simply continue: intersections, junctions and
+// dead ends
+pub struct ScaffoldGraph {
+    pub nodes: Vec<Pos>,
+    pub edges: Vec<Edge>,
+}
+
+fn step(image: &Image, (row, col): Pos, dir: Direction) -> Option<Pos> {
+    let (dr, dc) = dir.to_delta();
+    let (row, col) = (row as i64 + dr, col as i64 + dc);
+    if image.scaffold(row, col) {
+        Some((row as usize, col as usize))
+    } else {
+        None
+    }
+}
+
+fn scaffold_cells(image: &Image) -> impl Iterator<Item = Pos> + '_ {
+    image.map.iter().enumerate().flat_map(|(row, pixels)| {
+        pixels
+            .iter()
+            .enumerate()
+            .filter(|&(_, &scaffold)| scaffold)
+            .map(move |(col, _)| (row, col))
+    })
+}
+
+pub fn classify(image: &Image, pos: Pos) -> Option<Feature> {
+    if !image.scaffold(pos.0 as i64, pos.1 as i64) {
+        return None;
+    }
+    let open: Vec<bool> = DIRECTIONS
+        .iter()
+        .map(|&dir| step(image, pos, dir).is_some())
+        .collect();
+    Some(match open.iter().filter(|&&open| open).count() {
+        4 => Feature::Intersection,
+        3 => Feature::Junction,
+        1 => Feature::DeadEnd,
+        0 => Feature::Isolated,
+        // up and down, or left and right
+        _ if open[0] == open[2] => Feature::Straight,
+        _ => Feature::Corner,
+    })
+}
+
+// every scaffold cell that is not part of a straight line
+pub fn features(image: &Image) -> Vec<(Pos, Feature)> {
+    scaffold_cells(image)
+        .filter_map(|pos| classify(image, pos).map(|feature| (pos, feature)))
+        .filter(|&(_, feature)| feature != Feature::Straight)
+        .collect()
+}
+
+pub fn segments(image: &Image) -> Vec<Segment> {
+    let mut res = Vec::new();
+    for &(dir, orientation) in [
+        (Direction::Right, Orientation::Horizontal),
+        (Direction::Down, Orientation::Vertical),
+    ]
+    .iter()
+    {
+        let back = dir.left().left();
+        for start in scaffold_cells(image) {
+            // only start from the top or left end of a run
+            if step(image, start, back).is_some() {
+                continue;
+            }
+            let (mut end, mut length) = (start, 0);
+            while let Some(next) = step(image, end, dir) {
+                end = next;
+                length += 1;
+            }
+            if length > 0 {
+                res.push(Segment {
+                    start,
+                    end,
+                    orientation,
+                    length,
+                });
+            }
+        }
+    }
+    res
+}
+
+pub fn scaffold_graph(image: &Image) -> ScaffoldGraph {
+    let nodes: Vec<Pos> = scaffold_cells(image)
+        .filter(|&pos| {
+            !matches!(
+                classify(image, pos),
+                Some(Feature::Straight) | Some(Feature::Corner)
+            )
+        })
+        .collect();
+    let index: HashMap<Pos, usize> = nodes.iter().enumerate().map(|(i, &pos)| (pos, i)).collect();
+
+    let mut edges = Vec::new();
+    for (from, &start) in nodes.iter().enumerate() {
+        for (start_dir, &first_dir) in DIRECTIONS.iter().enumerate() {
+            let mut pos = match step(image, start, first_dir) {
+                Some(pos) => pos,
+                None => continue,
+            };
+            let mut dir = first_dir;
+            let mut legs = vec![(dir, 1)];
+            while !index.contains_key(&pos) {
+                // a straight or corner cell, so there is exactly one way on
+                if step(image, pos, dir).is_none() {
+                    dir = [dir.left(), dir.right()]
+                        .iter()
+                        .cloned()
+                        .find(|&turn| step(image, pos, turn).is_some())
+                        .unwrap();
+                    legs.push((dir, 0));
+                }
+                pos = step(image, pos, dir).unwrap();
+                legs.last_mut().unwrap().1 += 1;
+            }
+
+            // every edge is followed from both ends, so keep it from the end that sorts first
+            let to = index[&pos];
+            let end_dir = DIRECTIONS
+                .iter()
+                .position(|&d| d == dir.left().left())
+                .unwrap();
+            if (from, start_dir) <= (to, end_dir) {
+                edges.push(Edge { from, to, legs });
+            }
+        }
+    }
+
+    ScaffoldGraph { nodes, edges }
+}
+
+pub fn intersections(image: &Image) -> Vec<Pos> {
+    features(image)
+        .into_iter()
+        .filter(|&(_, feature)| feature == Feature::Intersection)
+        .map(|(pos, _)| pos)
+        .collect()
+}
+
+impl Display for ScaffoldGraph {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        for edge in self.edges.iter() {
+            let legs: Vec<String> = edge
+                .legs
+                .iter()
+                .map(|&(dir, steps)| {
+                    let c = match dir {
+                        Direction::Up => 'U',
+                        Direction::Down => 'D',
+                        Direction::Left => 'L',
+                        Direction::Right => 'R',
+                    };
+                    format!("{}{}", c, steps)
+                })
+                .collect();
+            writeln!(
+                f,
+                "{:?} -> {:?}: {} steps ({})",
+                self.nodes[edge.from],
+                self.nodes[edge.to],
+                edge.steps(),
+                legs.join(" ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{features, intersections, scaffold_graph, segments, Feature, Orientation};
+    use crate::parse_image;
+
+    // the example from part 1 of the puzzle
+    const EXAMPLE: &str = "..#..........\n\
+                           ..#..........\n\
+                           #######...###\n\
+                           #.#...#...#.#\n\
+                           #############\n\
+                           ..#...#...#..\n\
+                           ..#####...^..";
+
+    #[test]
+    fn test_features() {
+        let image = parse_image(EXAMPLE.lines()).unwrap();
+        assert_eq!(intersections(&image), vec![(2, 2), (4, 2), (4, 6), (4, 10)]);
+        let sum: usize = intersections(&image).iter().map(|(r, c)| r * c).sum();
+        assert_eq!(sum, 76);
+
+        let features = features(&image);
+        let count = |feature| features.iter().filter(|&&(_, f)| f == feature).count();
+        assert_eq!(count(Feature::DeadEnd), 2);
+        assert_eq!(count(Feature::Junction), 0);
+        assert_eq!(count(Feature::Corner), 8);
+    }
+
+    #[test]
+    fn test_segments() {
+        let image = parse_image(EXAMPLE.lines()).unwrap();
+        let segments = segments(&image);
+        let longest = segments.iter().max_by_key(|s| s.length).unwrap();
+        assert_eq!(longest.start, (4, 0));
+        assert_eq!(longest.end, (4, 12));
+        assert_eq!(longest.orientation, Orientation::Horizontal);
+        assert_eq!(longest.length, 12);
+        let vertical = segments
+            .iter()
+            .filter(|s| s.orientation == Orientation::Vertical)
+            .count();
+        assert_eq!(vertical, 5);
+    }
+
+    #[test]
+    fn test_graph() {
+        let image = parse_image(EXAMPLE.lines()).unwrap();
+        let graph = scaffold_graph(&image);
+        // four intersections and two dead ends
+        assert_eq!(graph.nodes.len(), 6);
+
+        // four intersections of degree four and two dead ends
+        assert_eq!(graph.edges.len(), (4 * 4 + 2) / 2);
+
+        // every step between neighbouring scaffold cells belongs to exactly one edge
+        let mut pairs = 0;
+        for (row, pixels) in image.map.iter().enumerate() {
+            for col in 0..pixels.len() {
+                let (row, col) = (row as i64, col as i64);
+                if image.scaffold(row, col) {
+                    pairs += image.scaffold(row + 1, col) as usize;
+                    pairs += image.scaffold(row, col + 1) as usize;
+                }
+            }
+        }
+        let steps: usize = graph.edges.iter().map(|edge| edge.steps()).sum();
+        assert_eq!(steps, pairs);
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::VecDeque;
 use std::iter;
 
 enum ParameterMode {
@@ -83,10 +84,20 @@ impl Program {
     }
 }
 
+#[derive(Debug)]
+pub enum Status {
+    Halted,
+    NeedsInput,
+    Output(i64),
+}
+
+#[derive(Clone)]
 pub struct Emulator {
     memory: Vec<i64>,
     ip: i64,
     relative_base: i64,
+    input: VecDeque<i64>,
+    instructions: u64,
 }
 
 impl Emulator {
@@ -95,9 +106,20 @@ impl Emulator {
             memory: program.memory,
             ip: 0,
             relative_base: 0,
+            input: VecDeque::new(),
+            instructions: 0,
         }
     }
 
+    pub fn add_input(&mut self, val: i64) {
+        self.input.push_back(val);
+    }
+
+    /// number of instructions executed so far
+    pub fn instructions(&self) -> u64 {
+        self.instructions
+    }
+
     fn get(&self, address: i64) -> i64 {
         let address = address as usize;
         if address >= self.memory.len() {
@@ -111,7 +133,7 @@ impl Emulator {
         let address = address as usize;
         if address >= self.memory.len() {
             self.memory
-                .extend(iter::repeat(0).take(address - self.memory.len() + 1))
+                .extend(iter::repeat_n(0, address - self.memory.len() + 1))
         }
         self.memory[address] = value;
     }
@@ -134,12 +156,8 @@ impl Emulator {
         })
     }
 
-    /// returns Ok(true) if halted
-    pub fn step(
-        &mut self,
-        get_input: impl FnOnce() -> Result<i64, &'static str>,
-        handle_output: impl FnOnce(i64) -> Result<(), &'static str>,
-    ) -> Result<bool, &'static str> {
+    /// returns Ok(None) if there is no special status
+    fn step(&mut self) -> Result<Option<Status>, &'static str> {
         let instr_code = self.get(self.ip);
         let instr = Instruction::parse(instr_code)?;
 
@@ -159,15 +177,19 @@ impl Emulator {
                 self.ip += 4;
             }
             Opcode::Input => {
-                let input = get_input()?;
+                let input = match self.input.pop_front() {
+                    Some(val) => val,
+                    None => return Ok(Some(Status::NeedsInput)),
+                };
                 let res_addr = self.get_arg_dest(1, instr.p1_mode)?;
                 self.store(res_addr, input);
                 self.ip += 2;
             }
             Opcode::Output => {
                 let arg = self.get_arg_val(1, instr.p1_mode);
-                handle_output(arg)?;
                 self.ip += 2;
+                self.instructions += 1;
+                return Ok(Some(Status::Output(arg)));
             }
             Opcode::JumpIfTrue => {
                 let cond = self.get_arg_val(1, instr.p1_mode);
@@ -207,10 +229,30 @@ impl Emulator {
                 self.ip += 2;
             }
             Opcode::Halt => {
-                return Ok(true);
+                return Ok(Some(Status::Halted));
             }
         }
 
-        Ok(false)
+        self.instructions += 1;
+        Ok(None)
+    }
+
+    pub fn run(&mut self) -> Result<Status, &'static str> {
+        loop {
+            if let Some(status) = self.step()? {
+                return Ok(status);
+            }
+        }
+    }
+
+    /// like run, but returns Ok(None) if `max_instructions` run without a special status
+    pub fn run_for(&mut self, max_instructions: u64) -> Result<Option<Status>, &'static str> {
+        let deadline = self.instructions + max_instructions;
+        while self.instructions < deadline {
+            if let Some(status) = self.step()? {
+                return Ok(Some(status));
+            }
+        }
+        Ok(None)
     }
 }
@@ -1,6 +1,17 @@
+mod analysis;
+mod ascii;
 mod emulator;
-
-#[derive(Copy, Clone)]
+mod video;
+mod walks;
+use crate::analysis::Feature;
+use crate::ascii::{AsciiComputer, Event};
+use crate::emulator::Emulator;
+use crate::video::{Feed, FrameReader};
+use crate::walks::find_walk;
+use std::env;
+use std::fmt::{Display, Error, Formatter};
+
+#[derive(Copy, Clone, Eq, PartialEq)]
 enum Direction {
     Left,
     Right,
@@ -45,6 +56,8 @@ struct Image {
     vacuum_row: usize,
     vacuum_col: usize,
     vacuum_dir: Direction,
+    // the vacuum robot fell off the scaffold, drawn as 'X'
+    tumbling: bool,
 }
 
 impl Image {
@@ -60,24 +73,40 @@ impl Image {
     }
 }
 
-fn get_image(emu: &mut emulator::Emulator) -> Result<Image, &'static str> {
+fn get_image(computer: &mut AsciiComputer) -> Result<Image, &'static str> {
+    let mut lines = Vec::new();
+    for event in computer.read_until_input()? {
+        match event {
+            Event::Line(line) => lines.push(line),
+            Event::Halted => break,
+            _ => return Err("unexpected camera output"),
+        }
+    }
+    parse_image(lines.iter().map(|line| line.as_str()))
+}
+
+fn parse_image<'a>(lines: impl Iterator<Item = &'a str>) -> Result<Image, &'static str> {
     let mut map = Vec::new();
-    let mut row = Box::new(Vec::new());
 
     let mut vacuum_row = 0;
     let mut vacuum_col = 0;
     let mut vacuum_dir = Direction::Left;
+    let mut tumbling = false;
+
+    for line in lines {
+        if line.is_empty() {
+            continue;
+        }
 
-    while !emu.step(
-        || Err("no input"),
-        |i| {
-            match (i as u8) as char {
+        let mut row = Vec::with_capacity(line.len());
+        for (col, c) in line.chars().enumerate() {
+            match c {
                 '.' => row.push(false),
                 '#' => row.push(true),
                 '^' | 'v' | '<' | '>' => {
-                    vacuum_col = row.len();
+                    vacuum_col = col;
                     vacuum_row = map.len();
-                    vacuum_dir = match (i as u8) as char {
+                    vacuum_dir = match c {
                         '^' => Direction::Up,
                         'v' => Direction::Down,
                         '<' => Direction::Left,
@@ -86,44 +115,60 @@ fn get_image(emu: &mut emulator::Emulator) -> Result<Image, &'static str> {
                     };
                     row.push(true);
                 }
-                '\n' => {
-                    if row.len() > 0 {
-                        map.push(std::mem::replace(&mut *row, Vec::new()));
-                    }
+                'X' => {
+                    vacuum_col = col;
+                    vacuum_row = map.len();
+                    tumbling = true;
+                    row.push(false);
                 }
                 _ => return Err("invalid pixel"),
             }
-            Ok(())
-        },
-    )? {}
+        }
+        map.push(row);
+    }
 
     Ok(Image {
         map,
         vacuum_row,
         vacuum_col,
         vacuum_dir,
+        tumbling,
     })
 }
 
-fn sum_intersection_alignment_params(image: &Image) -> usize {
-    let mut res = 0;
-
-    let height = image.map.len();
-    let width = image.map[0].len();
-    for row in 1..height - 1 {
-        for col in 1..width - 1 {
-            if image.map[row][col]
-                && image.map[row - 1][col]
-                && image.map[row + 1][col]
-                && image.map[row][col - 1]
-                && image.map[row][col + 1]
-            {
-                res += row * col;
+impl Display for Image {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        for (row, pixels) in self.map.iter().enumerate() {
+            for (col, &scaffold) in pixels.iter().enumerate() {
+                let c = if (row, col) != (self.vacuum_row, self.vacuum_col) {
+                    if scaffold {
+                        '#'
+                    } else {
+                        '.'
+                    }
+                } else if self.tumbling {
+                    'X'
+                } else {
+                    match self.vacuum_dir {
+                        Direction::Up => '^',
+                        Direction::Down => 'v',
+                        Direction::Left => '<',
+                        Direction::Right => '>',
+                    }
+                };
+                write!(f, "{}", c)?;
             }
+            writeln!(f)?;
         }
+        Ok(())
     }
+}
 
-    res
+fn sum_intersection_alignment_params(image: &Image) -> usize {
+    analysis::intersections(image)
+        .iter()
+        .map(|(row, col)| row * col)
+        .sum()
 }
 
 // path finding
@@ -135,12 +180,12 @@ enum Instruction {
     Move(usize),
 }
 
-impl ToString for Instruction {
-    fn to_string(&self) -> String {
+impl Display for Instruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
         match self {
-            Instruction::RotateLeft => "L".to_string(),
-            Instruction::RotateRight => "R".to_string(),
-            Instruction::Move(steps) => steps.to_string(),
+            Instruction::RotateLeft => write!(f, "L"),
+            Instruction::RotateRight => write!(f, "R"),
+            Instruction::Move(steps) => write!(f, "{}", steps),
         }
     }
 }
@@ -216,88 +261,118 @@ fn find_path(image: &Image) -> Option<Vec<Instruction>> {
 
 // movement routine generation
 
+// movement routines, with functions[0] called as A, functions[1] as B and so on
+#[derive(Debug, PartialEq)]
 struct PathProgram {
     main: String,
-    a: String,
-    b: String,
-    c: String,
+    functions: Vec<String>,
 }
 
-fn split<'a, T: PartialEq>(mut path: &'a [T], delim: &[T]) -> Vec<&'a [T]> {
-    let mut res = Vec::new();
-    let mut current_index = 0;
-    while path.len() - current_index >= delim.len() {
-        if &path[current_index..current_index + delim.len()] == delim {
-            if current_index > 0 {
-                res.push(&path[..current_index]);
-            }
-            path = &path[current_index + delim.len()..];
-            current_index = 0;
-        } else {
-            current_index += 1;
+// ASCII lengths exclude the trailing newline
+#[derive(Copy, Clone)]
+struct Limits {
+    main_chars: usize,
+    function_chars: usize,
+    functions: usize,
+}
+
+// the robot asks for exactly this many movement functions
+const ROBOT_FUNCTIONS: usize = 3;
+// functions are named A to Z
+const MAX_FUNCTIONS: usize = 26;
+
+impl Limits {
+    // limits no looser than the robot accepts
+    fn for_robot(self) -> Self {
+        Limits {
+            functions: self.functions.min(ROBOT_FUNCTIONS),
+            ..self
         }
     }
+}
 
-    if !path.is_empty() {
-        res.push(path);
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            main_chars: 20,
+            function_chars: 20,
+            functions: 3,
+        }
     }
+}
 
-    res
+struct Compressor<'a> {
+    limits: Limits,
+    functions: Vec<&'a [String]>,
+    calls: Vec<usize>,
+    res: Vec<PathProgram>,
 }
 
-fn compress_path(path: &[Instruction], max_chars: usize) -> Option<PathProgram> {
-    for a_end in 1..=path.len() {
-        let a = &path[..a_end];
-        let a_str = to_ascii(a);
-        if a.len() > max_chars {
-            break;
+impl<'a> Compressor<'a> {
+    // covers tokens[pos..] with calls, defining functions in the order they are first called
+    fn search(&mut self, tokens: &'a [String], pos: usize) {
+        if pos == tokens.len() {
+            let main: Vec<String> = self
+                .calls
+                .iter()
+                .map(|&i| ((b'A' + i as u8) as char).to_string())
+                .collect();
+            self.res.push(PathProgram {
+                main: main.join(","),
+                functions: self.functions.iter().map(|f| to_ascii(f)).collect(),
+            });
+            return;
+        }
+        if (self.calls.len() + 1) * 2 - 1 > self.limits.main_chars {
+            return;
         }
 
-        let remaining_chunks = split(path, a);
-        let first_chunk = remaining_chunks[0];
-        let other_chunks = &remaining_chunks[1..];
-
-        for b_end in 1..=first_chunk.len() {
-            let b = &first_chunk[..b_end];
-            let b_str = to_ascii(b);
-            if b_str.len() > max_chars {
-                break;
+        for i in 0..self.functions.len() {
+            if tokens[pos..].starts_with(self.functions[i]) {
+                self.calls.push(i);
+                self.search(tokens, pos + self.functions[i].len());
+                self.calls.pop();
             }
+        }
 
-            let mut remaining_chunks = split(first_chunk, b);
-            remaining_chunks.extend(other_chunks.iter().flat_map(|chunk| split(chunk, b)));
-            let first_chunk = remaining_chunks[0];
-
-            for c_end in 1..=first_chunk.len() {
-                let c = &first_chunk[..c_end];
-                let c_str = to_ascii(c);
-                if c_str.len() > max_chars {
+        if self.functions.len() < self.limits.functions {
+            for end in pos + 1..=tokens.len() {
+                let function = &tokens[pos..end];
+                let chars: usize = function.iter().map(|t| t.len() + 1).sum::<usize>() - 1;
+                if chars > self.limits.function_chars {
                     break;
                 }
-
-                if remaining_chunks
-                    .iter()
-                    .all(|chunk| split(chunk, c).is_empty())
-                {
-                    let main_str = to_ascii(path)
-                        .replace(&a_str, "A")
-                        .replace(&b_str, "B")
-                        .replace(&c_str, "C");
-
-                    if main_str.len() <= max_chars {
-                        return Some(PathProgram {
-                            main: main_str,
-                            a: a_str,
-                            b: b_str,
-                            c: c_str,
-                        });
-                    }
+                if self.functions.contains(&function) {
+                    continue;
                 }
+
+                self.calls.push(self.functions.len());
+                self.functions.push(function);
+                self.search(tokens, end);
+                self.functions.pop();
+                self.calls.pop();
             }
         }
     }
+}
+
+// returns every way of splitting the path into calls to at most `limits.functions` functions
+fn compress_path(path: &[Instruction], limits: Limits) -> Vec<PathProgram> {
+    let tokens: Vec<String> = path.iter().map(|i| i.to_string()).collect();
+    let mut compressor = Compressor {
+        limits,
+        functions: Vec::new(),
+        calls: Vec::new(),
+        res: Vec::new(),
+    };
+    compressor.search(&tokens, 0);
+    compressor.res
+}
 
-    None
+fn shortest_compression(path: &[Instruction], limits: Limits) -> Option<PathProgram> {
+    compress_path(path, limits)
+        .into_iter()
+        .min_by_key(|program| program.main.len())
 }
 
 fn to_ascii<T: ToString>(path: &[T]) -> String {
@@ -307,65 +382,270 @@ fn to_ascii<T: ToString>(path: &[T]) -> String {
         .join(",")
 }
 
+// passes each camera frame to `feed` if given
 fn exec_path_program(
-    emu: &mut emulator::Emulator,
+    computer: &mut AsciiComputer,
     path_program: PathProgram,
+    mut feed: Option<&mut Feed>,
 ) -> Result<i64, &'static str> {
-    let mut res = Box::new(None);
-    let input = format!(
-        "{}\n{}\n{}\n{}\nn\n",
-        path_program.main, path_program.a, path_program.b, path_program.c
-    );
-    let mut chars = input.chars();
-    while !emu.step(
-        || chars.next().ok_or("need more input").map(|c| c as i64),
-        |output| {
-            if output > 128 {
-                *res = Some(output);
+    // the robot always asks for three functions, so unused ones repeat the last
+    let function = |i: usize| {
+        path_program
+            .functions
+            .get(i)
+            .or_else(|| path_program.functions.last())
+            .map(|f| f.as_str())
+            .ok_or("no movement functions")
+    };
+    if path_program.functions.len() > ROBOT_FUNCTIONS {
+        return Err("too many movement functions");
+    }
+    let answers = [
+        ("Main", path_program.main.as_str()),
+        ("Function A", function(0)?),
+        ("Function B", function(1)?),
+        ("Function C", function(2)?),
+        (
+            "Continuous video feed",
+            if feed.is_some() { "y" } else { "n" },
+        ),
+    ];
+    for (prompt, answer) in answers.iter() {
+        computer.wait_for(prompt)?;
+        computer.send_line(answer);
+    }
+
+    let mut frames = FrameReader::new();
+    loop {
+        match computer.next_event()? {
+            Event::NonAscii(dust) => return Ok(dust),
+            Event::Line(line) => {
+                if let Some(feed) = feed.as_mut() {
+                    if let Some(image) = frames.line(line)? {
+                        feed.frame(&image)?;
+                    }
+                }
             }
-            Ok(())
-        },
-    )? {}
+            Event::NeedsInput => return Err("need more input"),
+            Event::Halted => return Err("no non-ASCII output"),
+        }
+    }
+}
 
-    res.ok_or("no non-ASCII output")
+fn print_analysis(image: &Image) {
+    let features = analysis::features(image);
+    for feature in [
+        Feature::Intersection,
+        Feature::Junction,
+        Feature::DeadEnd,
+        Feature::Corner,
+    ]
+    .iter()
+    {
+        let count = features.iter().filter(|(_, f)| f == feature).count();
+        println!("{:?}s: {}", feature, count);
+    }
+
+    let segments = analysis::segments(image);
+    if let Some(longest) = segments.iter().max_by_key(|segment| segment.length) {
+        println!(
+            "{} straight segments, the longest {:?} from {:?} to {:?} ({} steps)",
+            segments.len(),
+            longest.orientation,
+            longest.start,
+            longest.end,
+            longest.length
+        );
+    }
+
+    let graph = analysis::scaffold_graph(image);
+    println!(
+        "graph with {} nodes and {} edges:",
+        graph.nodes.len(),
+        graph.edges.len()
+    );
+    print!("{}", graph);
 }
 
 fn main() {
+    let usage = "usage: day17 [--walks] [--main-chars <n>] [--function-chars <n>] \
+                 [--functions <n>] [--video [--fps <n>]] [--video-dir <dir>] [--analyze]";
+    let mut walks = false;
+    let mut analyze = false;
+    let mut limits = Limits::default();
+    let mut video = false;
+    let mut fps = None;
+    let mut video_dir = None;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().expect(usage);
+        let mut number = || -> usize { value().parse().expect(usage) };
+        match arg.as_str() {
+            "--walks" => walks = true,
+            "--analyze" => analyze = true,
+            "--video" => video = true,
+            "--fps" => {
+                let rate = number() as u32;
+                assert!(rate > 0, "invalid frame rate");
+                fps = Some(rate)
+            }
+            "--video-dir" => video_dir = Some(value()),
+            "--main-chars" => limits.main_chars = number(),
+            "--function-chars" => limits.function_chars = number(),
+            "--functions" => {
+                let functions = number();
+                assert!(functions <= MAX_FUNCTIONS, "too many movement functions");
+                limits.functions = functions
+            }
+            _ => panic!("{}", usage),
+        }
+    }
+
     let input = include_str!("input.txt");
     let program = emulator::Program::new(input).expect("parsing failed");
 
     // part 1
-    let mut emu = emulator::Emulator::new(program.clone());
-    let image = get_image(&mut emu).expect("failed to get image");
+    let mut computer = AsciiComputer::new(program.clone());
+    let image = get_image(&mut computer).expect("failed to get image");
     let sum = sum_intersection_alignment_params(&image);
     println!("Part 1: sum of alignment params = {}", sum);
 
+    if analyze {
+        print_analysis(&image);
+    }
+
+    if walks {
+        let (mut walks, mut compressible) = (0, 0);
+        find_walk(&image, |walk| {
+            walks += 1;
+            if !compress_path(walk, limits).is_empty() {
+                compressible += 1;
+            }
+            None::<()>
+        });
+        println!(
+            "{} walks cover the scaffold, {} of them compress",
+            walks, compressible
+        );
+    }
+
     // part 2
-    let mut emu = emulator::Emulator::new(program);
+    let mut emu = Emulator::new(program);
     emu.store(0, 2);
-    let path = find_path(&image).expect("failed to find path");
-    let path_program = compress_path(&path, 20).expect("failed to compress path");
-    let dust_collected =
-        exec_path_program(&mut emu, path_program).expect("failed to count dust collected");
+    let mut computer = AsciiComputer::with_emulator(emu);
+    // fall back to trying every walk over the scaffold when the greedy path does not compress
+    let limits = limits.for_robot();
+    let path_program = find_path(&image)
+        .and_then(|path| shortest_compression(&path, limits))
+        .or_else(|| find_walk(&image, |walk| shortest_compression(walk, limits)))
+        .expect("failed to compress path");
+    let mut feed = None;
+    if video || video_dir.is_some() {
+        let mut f = Feed::new();
+        if video {
+            f = f.show(fps);
+        }
+        if let Some(dir) = video_dir {
+            f = f.write_to(dir);
+        }
+        feed = Some(f);
+    }
+    let dust_collected = exec_path_program(&mut computer, path_program, feed.as_mut())
+        .expect("failed to count dust collected");
     println!("Part 2: dust collected = {}", dust_collected);
 }
 
 #[cfg(test)]
 mod tests {
-    use super::split;
+    use super::{compress_path, shortest_compression, Instruction, Limits, PathProgram};
+
+    fn parse_path(path: &str) -> Vec<Instruction> {
+        path.split(',')
+            .map(|token| match token {
+                "L" => Instruction::RotateLeft,
+                "R" => Instruction::RotateRight,
+                steps => Instruction::Move(steps.parse().unwrap()),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_compress_example() {
+        // the example from the puzzle
+        let path = parse_path("R,8,R,8,R,4,R,4,R,8,L,6,L,2,R,4,R,4,R,8,R,8,R,8,L,6,L,2");
+        let programs = compress_path(&path, Limits::default());
+        let expected = PathProgram {
+            main: "A,B,C,B,A,C".to_string(),
+            functions: vec![
+                "R,8,R,8".to_string(),
+                "R,4,R,4,R,8".to_string(),
+                "L,6,L,2".to_string(),
+            ],
+        };
+        assert!(programs.contains(&expected));
+        for program in programs.iter() {
+            assert!(program.main.len() <= 20);
+            assert!(program.functions.len() <= 3);
+            assert!(program.functions.iter().all(|f| f.len() <= 20));
+        }
+
+        // a longer first function takes in most of the path
+        let shortest = shortest_compression(&path, Limits::default()).unwrap();
+        assert_eq!(shortest.main, "A,B,C");
+    }
+
+    #[test]
+    fn test_compress_limits() {
+        let path = parse_path("L,4,R,4,L,4,R,4,L,4,R,4");
+        let limits = Limits {
+            main_chars: 5,
+            function_chars: 20,
+            functions: 1,
+        };
+        let programs = compress_path(&path, limits);
+        let mains: Vec<&str> = programs.iter().map(|p| p.main.as_str()).collect();
+        assert_eq!(mains, vec!["A,A,A"]);
+        assert_eq!(programs[0].functions, vec!["L,4,R,4"]);
+
+        // the whole path is too long for a single function
+        let limits = Limits {
+            main_chars: 1,
+            ..limits
+        };
+        assert!(compress_path(&path, limits).is_empty());
+
+        let limits = Limits {
+            function_chars: 30,
+            ..limits
+        };
+        let program = shortest_compression(&path, limits).unwrap();
+        assert_eq!(program.main, "A");
+
+        let limits = Limits {
+            functions: 0,
+            ..limits
+        };
+        assert!(compress_path(&path, limits).is_empty());
+    }
 
     #[test]
-    fn test_split() {
-        let instrs = [1, 2, 3, 4, 5, 3, 2, 1, 4, 5, 1];
-        let delim = [4, 5];
-        let res = split(&instrs, &delim);
-        let expected: Vec<&[i32]> = vec![&[1, 2, 3], &[3, 2, 1], &[1]];
-        assert_eq!(res, expected);
-
-        let instrs = [1, 1, 1];
-        let delim = [1];
-        let res = split(&instrs, &delim);
-        let expected: Vec<&[i32]> = vec![];
-        assert_eq!(res, expected);
+    fn test_robot_limits() {
+        // each function can only hold one turn and move
+        let path = parse_path("L,1,L,2,L,3,L,4");
+        let limits = Limits {
+            main_chars: 20,
+            function_chars: 3,
+            functions: 5,
+        };
+        let program = shortest_compression(&path, limits).unwrap();
+        assert_eq!(program.functions.len(), 4);
+
+        // the robot only takes three functions
+        assert_eq!(limits.for_robot().functions, 3);
+        assert!(shortest_compression(&path, limits.for_robot()).is_none());
+        let path = parse_path("L,1,L,2,L,3,L,1");
+        let program = shortest_compression(&path, limits.for_robot()).unwrap();
+        assert_eq!(program.main, "A,B,C,A");
     }
 }
@@ -0,0 +1,110 @@
+// continuous video feed from the vacuum robot's camera
+
+use crate::{parse_image, Image};
+use std::fs;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+// collects camera lines into frames, each of which ends with an empty line
+pub struct FrameReader {
+    lines: Vec<String>,
+}
+
+impl FrameReader {
+    pub fn new() -> Self {
+        FrameReader { lines: Vec::new() }
+    }
+
+    // returns the frame this line completes, if any; lines that are not part of the camera image,
+    // such as messages, are skipped
+    pub fn line(&mut self, line: String) -> Result<Option<Image>, &'static str> {
+        if line.is_empty() {
+            if self.lines.is_empty() {
+                return Ok(None);
+            }
+            let image = parse_image(self.lines.iter().map(|line| line.as_str()))?;
+            self.lines.clear();
+            return Ok(Some(image));
+        }
+
+        if line.chars().all(|c| ".#^v<>X".contains(c)) {
+            self.lines.push(line);
+        }
+        Ok(None)
+    }
+}
+
+// shows frames in the terminal and writes them to a directory as frame_00000.txt, ...
+pub struct Feed {
+    show: bool,
+    fps: Option<u32>,
+    dir: Option<PathBuf>,
+    frame: usize,
+}
+
+impl Feed {
+    pub fn new() -> Self {
+        Feed {
+            show: false,
+            fps: None,
+            dir: None,
+            frame: 0,
+        }
+    }
+
+    // draws each frame, waiting between them if `fps` is given
+    pub fn show(mut self, fps: Option<u32>) -> Self {
+        self.show = true;
+        self.fps = fps;
+        self
+    }
+
+    pub fn write_to(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dir = Some(dir.into());
+        self
+    }
+
+    pub fn frame(&mut self, image: &Image) -> Result<(), &'static str> {
+        if self.show {
+            print!("\x1b[H\x1b[2J{}", image);
+            if let Some(fps) = self.fps {
+                thread::sleep(Duration::from_secs(1) / fps);
+            }
+        }
+        if let Some(dir) = &self.dir {
+            fs::create_dir_all(dir).map_err(|_| "failed to create frame directory")?;
+            let path = dir.join(format!("frame_{:05}.txt", self.frame));
+            fs::write(path, image.to_string()).map_err(|_| "failed to write frame")?;
+        }
+        self.frame += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FrameReader;
+
+    #[test]
+    fn test_frames() {
+        let feed = [
+            "", "..#..", "..^..", "", "..#..", "..v..", "", "Oh no!", "..X..", "..#..", "",
+        ];
+        let mut reader = FrameReader::new();
+        let mut frames = Vec::new();
+        for line in feed.iter() {
+            if let Some(frame) = reader.line(line.to_string()).unwrap() {
+                frames.push(frame);
+            }
+        }
+
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[0].to_string(), "..#..\n..^..\n");
+        assert!(!frames[0].tumbling);
+        assert!(frames[2].tumbling);
+        assert_eq!((frames[2].vacuum_row, frames[2].vacuum_col), (0, 2));
+        assert!(!frames[2].scaffold(0, 2));
+        assert_eq!(frames[2].to_string(), "..X..\n..#..\n");
+    }
+}
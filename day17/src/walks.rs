@@ -0,0 +1,168 @@
+// enumerate every walk that covers the scaffold
+//
+// A walk starts at the vacuum robot and crosses each scaffold edge, between two neighbouring
+// scaffold cells, exactly once. At every intersection it may go straight or turn onto any edge it
+// has not crossed yet.
+
+use crate::{Direction, Image, Instruction};
+use std::collections::HashSet;
+
+type Cell = (i64, i64);
+
+struct Walker<'a> {
+    image: &'a Image,
+    edges: usize,
+    crossed: HashSet<(Cell, Cell)>,
+    dirs: Vec<Direction>,
+}
+
+impl Walker<'_> {
+    fn search<T>(
+        &mut self,
+        (row, col): Cell,
+        dir: Direction,
+        f: &mut dyn FnMut(&[Instruction]) -> Option<T>,
+    ) -> Option<T> {
+        if self.crossed.len() == self.edges {
+            return f(&to_instructions(self.image.vacuum_dir, &self.dirs));
+        }
+
+        // straight on first, like the greedy path
+        for &next_dir in [dir, dir.left(), dir.right(), dir.left().left()].iter() {
+            let (dr, dc) = next_dir.to_delta();
+            let next = (row + dr, col + dc);
+            let edge = ((row, col).min(next), (row, col).max(next));
+            if !self.image.scaffold(next.0, next.1) || self.crossed.contains(&edge) {
+                continue;
+            }
+
+            self.crossed.insert(edge);
+            self.dirs.push(next_dir);
+            let res = self.search(next, next_dir, f);
+            self.dirs.pop();
+            self.crossed.remove(&edge);
+            if res.is_some() {
+                return res;
+            }
+        }
+
+        None
+    }
+}
+
+// calls `f` with each walk over the whole scaffold, stopping at the first walk it returns Some for
+pub fn find_walk<T>(image: &Image, mut f: impl FnMut(&[Instruction]) -> Option<T>) -> Option<T> {
+    let mut edges = 0;
+    for (row, cells) in image.map.iter().enumerate() {
+        for col in 0..cells.len() {
+            let (row, col) = (row as i64, col as i64);
+            if image.scaffold(row, col) {
+                edges +=
+                    image.scaffold(row + 1, col) as usize + image.scaffold(row, col + 1) as usize;
+            }
+        }
+    }
+
+    let mut walker = Walker {
+        image,
+        edges,
+        crossed: HashSet::new(),
+        dirs: Vec::new(),
+    };
+    let start = (image.vacuum_row as i64, image.vacuum_col as i64);
+    walker.search(start, image.vacuum_dir, &mut f)
+}
+
+// turns the direction of each step into rotations and moves
+fn to_instructions(mut facing: Direction, dirs: &[Direction]) -> Vec<Instruction> {
+    let mut res = Vec::new();
+    for &dir in dirs {
+        if dir != facing {
+            if dir == facing.left() {
+                res.push(Instruction::RotateLeft);
+            } else {
+                // turning right twice to face back
+                res.push(Instruction::RotateRight);
+                if dir != facing.right() {
+                    res.push(Instruction::RotateRight);
+                }
+            }
+            facing = dir;
+        }
+
+        match res.last_mut() {
+            Some(Instruction::Move(steps)) => *steps += 1,
+            _ => res.push(Instruction::Move(1)),
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::find_walk;
+    use crate::{find_path, parse_image, shortest_compression, to_ascii, Instruction, Limits};
+
+    const EXAMPLE: &str = "#######...#####\n\
+                           #.....#...#...#\n\
+                           #.....#...#...#\n\
+                           ......#...#...#\n\
+                           ......#...###.#\n\
+                           ......#.....#.#\n\
+                           ^########...#.#\n\
+                           ......#.#...#.#\n\
+                           ......#########\n\
+                           ........#...#..\n\
+                           ....#########..\n\
+                           ....#...#......\n\
+                           ....#...#......\n\
+                           ....#...#......\n\
+                           ....#####......";
+
+    #[test]
+    fn test_walks() {
+        let image = parse_image(EXAMPLE.lines()).unwrap();
+
+        let mut walks = Vec::new();
+        find_walk(&image, |walk| {
+            walks.push(to_ascii(walk));
+            let steps: usize = walk
+                .iter()
+                .map(|instr| match instr {
+                    Instruction::Move(steps) => *steps,
+                    _ => 0,
+                })
+                .sum();
+            assert_eq!(steps, 80);
+            None::<()>
+        });
+
+        // the path straight through every intersection comes first
+        let greedy = to_ascii(&find_path(&image).unwrap());
+        assert_eq!(walks[0], greedy);
+        assert!(walks.len() > 1);
+        let unique: std::collections::HashSet<_> = walks.iter().collect();
+        assert_eq!(unique.len(), walks.len());
+    }
+
+    #[test]
+    fn test_compress_walk() {
+        let image = parse_image(EXAMPLE.lines()).unwrap();
+        let mut walk = String::new();
+        let program = find_walk(&image, |w| {
+            walk = to_ascii(w);
+            shortest_compression(w, Limits::default())
+        })
+        .unwrap();
+
+        assert!(program.main.len() <= 20);
+        assert!(program.functions.iter().all(|f| f.len() <= 20));
+        let expanded = program
+            .main
+            .split(',')
+            .map(|name| program.functions[(name.as_bytes()[0] - b'A') as usize].as_str())
+            .collect::<Vec<_>>()
+            .join(",");
+        assert_eq!(expanded, walk);
+    }
+}
@@ -0,0 +1,253 @@
+// answering whether a position is pulled by the tractor beam
+
+use crate::emulator::{Emulator, Program, Status};
+use std::collections::HashMap;
+use std::thread;
+
+pub trait Oracle {
+    fn query(&mut self, x: usize, y: usize) -> Result<bool, &'static str>;
+    // number of queries that ran the drone program
+    fn calls(&self) -> usize;
+    // number of queries answered without running it
+    fn hits(&self) -> usize {
+        0
+    }
+
+    fn query_batch(&mut self, positions: &[(usize, usize)]) -> Result<Vec<bool>, &'static str> {
+        positions.iter().map(|&(x, y)| self.query(x, y)).collect()
+    }
+}
+
+// deploys a fresh drone for every query
+pub struct ProgramOracle {
+    program: Program,
+    calls: usize,
+}
+
+impl ProgramOracle {
+    pub fn new(program: Program) -> Self {
+        ProgramOracle { program, calls: 0 }
+    }
+}
+
+impl Oracle for ProgramOracle {
+    fn query(&mut self, x: usize, y: usize) -> Result<bool, &'static str> {
+        self.calls += 1;
+        check_pos(self.program.clone(), x, y)
+    }
+
+    fn calls(&self) -> usize {
+        self.calls
+    }
+}
+
+// runs the drone program's setup once, and starts every query from a copy of the emulator waiting
+// for its first input; answers are cached per position
+pub struct SnapshotOracle {
+    snapshot: Emulator,
+    cache: HashMap<(usize, usize), bool>,
+    calls: usize,
+    hits: usize,
+    threads: usize,
+}
+
+impl SnapshotOracle {
+    pub fn new(program: Program) -> Result<Self, &'static str> {
+        let mut snapshot = Emulator::new(program);
+        match snapshot.run()? {
+            Status::NeedsInput => {}
+            _ => return Err("drone program did not ask for a position"),
+        }
+        Ok(SnapshotOracle {
+            snapshot,
+            cache: HashMap::new(),
+            calls: 0,
+            hits: 0,
+            threads: 1,
+        })
+    }
+
+    // number of threads batched queries are spread over
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+}
+
+fn run_snapshot(snapshot: &Emulator, x: usize, y: usize) -> Result<bool, &'static str> {
+    let mut emu = snapshot.clone();
+    emu.add_input(x as i64);
+    emu.add_input(y as i64);
+    match emu.run()? {
+        Status::Output(1) => Ok(true),
+        Status::Output(0) => Ok(false),
+        _ => Err("unexpected emulator output"),
+    }
+}
+
+impl Oracle for SnapshotOracle {
+    fn query(&mut self, x: usize, y: usize) -> Result<bool, &'static str> {
+        if let Some(&res) = self.cache.get(&(x, y)) {
+            self.hits += 1;
+            return Ok(res);
+        }
+        self.calls += 1;
+        let res = run_snapshot(&self.snapshot, x, y)?;
+        self.cache.insert((x, y), res);
+        Ok(res)
+    }
+
+    fn calls(&self) -> usize {
+        self.calls
+    }
+
+    fn hits(&self) -> usize {
+        self.hits
+    }
+
+    fn query_batch(&mut self, positions: &[(usize, usize)]) -> Result<Vec<bool>, &'static str> {
+        let mut missing: Vec<(usize, usize)> = positions
+            .iter()
+            .filter(|pos| !self.cache.contains_key(pos))
+            .cloned()
+            .collect();
+        missing.sort_unstable();
+        missing.dedup();
+
+        let chunk_size = missing.len().div_ceil(self.threads).max(1);
+        let snapshot = &self.snapshot;
+        let results = thread::scope(|scope| {
+            let handles: Vec<_> = missing
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|&(x, y)| run_snapshot(snapshot, x, y))
+                            .collect::<Result<Vec<bool>, &'static str>>()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().map_err(|_| "query thread panicked")?)
+                .collect::<Result<Vec<Vec<bool>>, &'static str>>()
+        })?;
+
+        self.calls += missing.len();
+        self.hits += positions.len() - missing.len();
+        self.cache
+            .extend(missing.into_iter().zip(results.into_iter().flatten()));
+        Ok(positions.iter().map(|pos| self.cache[pos]).collect())
+    }
+}
+
+pub fn check_pos(program: Program, x: usize, y: usize) -> Result<bool, &'static str> {
+    let mut emu = Emulator::new(program);
+    emu.add_input(x as i64);
+    emu.add_input(y as i64);
+    match emu.run()? {
+        Status::Output(1) => Ok(true),
+        Status::Output(0) => Ok(false),
+        _ => Err("unexpected emulator output"),
+    }
+}
+
+// beam between two lines through the origin, x = y * left.0 / left.1 and x = y * right.0 / right.1,
+// for testing without a drone program
+#[cfg(test)]
+pub struct LineOracle {
+    pub left: (usize, usize),
+    pub right: (usize, usize),
+    pub calls: usize,
+}
+
+#[cfg(test)]
+impl LineOracle {
+    pub fn new(left: (usize, usize), right: (usize, usize)) -> Self {
+        LineOracle {
+            left,
+            right,
+            calls: 0,
+        }
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        // the origin is always in the beam
+        (x, y) == (0, 0)
+            || (x * self.left.1 >= y * self.left.0 && x * self.right.1 <= y * self.right.0)
+    }
+}
+
+#[cfg(test)]
+impl Oracle for LineOracle {
+    fn query(&mut self, x: usize, y: usize) -> Result<bool, &'static str> {
+        self.calls += 1;
+        Ok(self.contains(x, y))
+    }
+
+    fn calls(&self) -> usize {
+        self.calls
+    }
+}
+
+// drone program for the beam of LineOracle::new(left, right), which spends a while counting down
+// before asking for a position
+#[cfg(test)]
+pub fn beam_program(left: (usize, usize), right: (usize, usize)) -> Program {
+    let code = format!(
+        "1101,0,200,99,1001,99,-1,99,1005,99,4,\
+         3,100,3,101,\
+         1002,100,{},102,1002,101,{},103,7,102,103,104,\
+         1002,100,{},105,1002,101,{},106,7,106,105,107,\
+         1,104,107,108,1008,108,0,109,4,109,99",
+        left.1, left.0, right.1, right.0
+    );
+    Program::new(&code).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{beam_program, LineOracle, Oracle, ProgramOracle, SnapshotOracle};
+
+    #[test]
+    fn test_snapshot() {
+        let beam = LineOracle::new((1, 2), (3, 4));
+        let mut fresh = ProgramOracle::new(beam_program((1, 2), (3, 4)));
+        let mut snapshot = SnapshotOracle::new(beam_program((1, 2), (3, 4))).unwrap();
+        for y in 0..20 {
+            for x in 0..20 {
+                assert_eq!(fresh.query(x, y).unwrap(), beam.contains(x, y));
+                assert_eq!(snapshot.query(x, y).unwrap(), beam.contains(x, y));
+            }
+        }
+        assert_eq!(snapshot.calls(), 400);
+
+        // asking again only reads the cache
+        snapshot.query(5, 7).unwrap();
+        assert_eq!(snapshot.calls(), 400);
+        assert_eq!(snapshot.hits(), 1);
+    }
+
+    #[test]
+    fn test_batch() {
+        let beam = LineOracle::new((7, 10), (8, 10));
+        let mut oracle = SnapshotOracle::new(beam_program((7, 10), (8, 10)))
+            .unwrap()
+            .with_threads(4);
+        oracle.query(3, 4).unwrap();
+
+        let positions: Vec<(usize, usize)> = (0..30)
+            .flat_map(|y| (0..30).map(move |x| (x, y)))
+            .chain(Some((3, 4)))
+            .collect();
+        let res = oracle.query_batch(&positions).unwrap();
+        let expected: Vec<bool> = positions
+            .iter()
+            .map(|&(x, y)| beam.contains(x, y))
+            .collect();
+        assert_eq!(res, expected);
+        assert_eq!(oracle.calls(), 900);
+        assert_eq!(oracle.hits(), 2);
+    }
+}
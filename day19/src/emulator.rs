@@ -91,6 +91,7 @@ pub enum Status {
     Output(i64),
 }
 
+#[derive(Clone)]
 pub struct Emulator {
     memory: Vec<i64>,
     ip: i64,
@@ -125,7 +126,7 @@ impl Emulator {
         let address = address as usize;
         if address >= self.memory.len() {
             self.memory
-                .extend(iter::repeat(0).take(address - self.memory.len() + 1))
+                .extend(iter::repeat_n(0, address - self.memory.len() + 1))
         }
         self.memory[address] = value;
     }
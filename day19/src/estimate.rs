@@ -0,0 +1,279 @@
+// predicting where a box first fits from the slopes of the beam's edges
+//
+// The beam lies between two lines through the origin. Fitting their slopes to the edges of a few
+// rows gives a good guess for the first row a box fits in, which is then checked, and corrected,
+// with exact queries around it.
+
+use crate::beam::Oracle;
+use std::collections::HashMap;
+use std::ops::Range;
+
+// rows sampled before the first prediction, from the first to the last doubling each time
+const FIRST_SAMPLE: usize = 10;
+const LAST_SAMPLE: usize = 80;
+
+pub struct Estimator<O> {
+    oracle: O,
+    // inclusive (left, right) edges of each row found so far, None for rows the beam misses
+    rows: HashMap<usize, Option<(usize, usize)>>,
+    // (y, left, right) of each row the slopes are fitted to
+    samples: Vec<(usize, usize, usize)>,
+    // x / y of the left and right edges
+    slopes: Option<(f64, f64)>,
+    // furthest the beam's right edge moves per row
+    max_step: usize,
+    // rows above a fit that are checked for another one, since edges rounded to whole cells can
+    // make the rows a box fits in start and stop near the origin
+    margin: usize,
+    max_rows: usize,
+}
+
+impl<O: Oracle> Estimator<O> {
+    pub fn new(oracle: O) -> Self {
+        Estimator {
+            oracle,
+            rows: HashMap::new(),
+            samples: Vec::new(),
+            slopes: None,
+            max_step: 10,
+            margin: 5,
+            max_rows: 100_000,
+        }
+    }
+
+    pub fn with_max_step(mut self, max_step: usize) -> Self {
+        self.max_step = max_step;
+        self
+    }
+
+    pub fn oracle(&self) -> &O {
+        &self.oracle
+    }
+
+    // the fitted (left, right) slopes, if any row has been sampled yet
+    pub fn slopes(&self) -> Option<(f64, f64)> {
+        self.slopes
+    }
+
+    fn probe(&mut self, x: i64, y: usize) -> Result<bool, &'static str> {
+        Ok(x >= 0 && self.oracle.query(x as usize, y)?)
+    }
+
+    // returns the last x in the beam going from `inside` in steps of `dir`
+    fn edge(&mut self, y: usize, inside: i64, dir: i64) -> Result<i64, &'static str> {
+        let (mut inside, mut step) = (inside, 1);
+        let mut outside = loop {
+            let x = inside + dir * step;
+            if !self.probe(x, y)? {
+                break x;
+            }
+            inside = x;
+            step *= 2;
+        };
+        while (outside - inside).abs() > 1 {
+            let mid = (inside + outside) / 2;
+            if self.probe(mid, y)? {
+                inside = mid;
+            } else {
+                outside = mid;
+            }
+        }
+        Ok(inside)
+    }
+
+    // returns the inclusive (left, right) edges of row y, or None if the beam misses it
+    pub fn edges(&mut self, y: usize) -> Result<Option<(usize, usize)>, &'static str> {
+        if y >= self.max_rows {
+            return Err("row out of range");
+        }
+        if let Some(&edges) = self.rows.get(&y) {
+            return Ok(edges);
+        }
+
+        // look for any cell in the beam, outwards from the middle of the predicted row
+        let limit = (self.max_step * (y + 1)) as i64;
+        let guess = match self.slopes {
+            Some((left, right)) => (((left + right) / 2.0 * y as f64).round() as i64).min(limit),
+            None => 0,
+        };
+        let mut inside = None;
+        for d in 0..=limit {
+            for &x in [guess + d, guess - d].iter() {
+                if x <= limit && self.probe(x, y)? {
+                    inside = Some(x);
+                    break;
+                }
+            }
+            if inside.is_some() || (guess - d < 0 && guess + d > limit) {
+                break;
+            }
+        }
+
+        let edges = match inside {
+            Some(x) => Some((self.edge(y, x, -1)? as usize, self.edge(y, x, 1)? as usize)),
+            None => None,
+        };
+        self.rows.insert(y, edges);
+        Ok(edges)
+    }
+
+    // adds the edges of row y to the samples and fits the slopes again
+    fn sample(&mut self, y: usize) -> Result<(), &'static str> {
+        let (left, right) = match self.edges(y)? {
+            Some(edges) => edges,
+            None => return Ok(()),
+        };
+        if y == 0 || self.samples.iter().any(|&(sy, _, _)| sy == y) {
+            return Ok(());
+        }
+        self.samples.push((y, left, right));
+
+        // least squares for lines through the origin
+        let sum = |f: &dyn Fn(&(usize, usize, usize)) -> usize| -> f64 {
+            self.samples.iter().map(|s| (f(s) * s.0) as f64).sum()
+        };
+        let yy = sum(&|s| s.0);
+        self.slopes = Some((sum(&|s| s.1) / yy, sum(&|s| s.2) / yy));
+        Ok(())
+    }
+
+    // the row a width x height box first fits in going by the slopes alone
+    fn predict(&self, width: usize, height: usize) -> Result<usize, &'static str> {
+        let (left, right) = self.slopes.ok_or("beam not sampled")?;
+        if right <= left {
+            return Err("beam does not widen");
+        }
+        // the bottom row's left edge must be width cells left of the top row's right edge
+        let y = (left * (height - 1) as f64 + (width - 1) as f64) / (right - left);
+        Ok((y.round() as usize).min(self.max_rows - height))
+    }
+
+    fn fits(&mut self, y: usize, width: usize, height: usize) -> Result<bool, &'static str> {
+        // both edges only move right from row to row, so the top row bounds the box's right side
+        // and the bottom row its left side
+        Ok(match (self.edges(y)?, self.edges(y + height - 1)?) {
+            (Some((_, right)), Some((left, _))) => right + 1 >= left + width,
+            _ => false,
+        })
+    }
+
+    fn first_fit(
+        &mut self,
+        rows: Range<usize>,
+        width: usize,
+        height: usize,
+    ) -> Result<Option<usize>, &'static str> {
+        for y in rows {
+            if self.fits(y, width, height)? {
+                return Ok(Some(y));
+            }
+        }
+        Ok(None)
+    }
+
+    // returns the top left corner of the nearest width x height box that fits in the beam
+    pub fn fit(&mut self, width: usize, height: usize) -> Result<(usize, usize), &'static str> {
+        if self.slopes.is_none() {
+            let mut y = FIRST_SAMPLE;
+            while y <= LAST_SAMPLE {
+                self.sample(y)?;
+                y *= 2;
+            }
+        }
+        let guess = self.predict(width, height)?;
+
+        // the slopes matter most around the box, so sample there before predicting again
+        self.sample(guess)?;
+        self.sample(guess + height - 1)?;
+        let mut y = self.predict(width, height)?;
+
+        while !self.fits(y, width, height)? {
+            y += 1;
+            if y + height > self.max_rows {
+                return Err("box does not fit in the beam");
+            }
+        }
+        while let Some(above) = self.first_fit(y.saturating_sub(self.margin)..y, width, height)? {
+            y = above;
+        }
+
+        let (left, _) = self.edges(y + height - 1)?.unwrap();
+        Ok((left, y))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Estimator;
+    use crate::beam::{LineOracle, Oracle};
+    use crate::tracker::EdgeTracker;
+
+    const BEAMS: [((usize, usize), (usize, usize)); 4] = [
+        ((1, 2), (3, 4)),
+        ((7, 10), (8, 10)),
+        ((13, 10), (21, 10)),
+        ((11, 23), (37, 41)),
+    ];
+
+    #[test]
+    fn test_edges() {
+        for &(left, right) in BEAMS.iter() {
+            let beam = LineOracle::new(left, right);
+            let mut estimator = Estimator::new(LineOracle::new(left, right));
+            for &y in [0, 1, 2, 3, 10, 57, 300, 299, 1000].iter() {
+                let expected: Vec<usize> =
+                    (0..3 * y + 1).filter(|&x| beam.contains(x, y)).collect();
+                let edges = estimator.edges(y).unwrap();
+                assert_eq!(
+                    edges,
+                    expected.first().map(|&l| (l, *expected.last().unwrap()))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_fit() {
+        let ships = [
+            (1, 1),
+            (10, 10),
+            (5, 12),
+            (12, 5),
+            (100, 100),
+            (30, 70),
+            (70, 30),
+        ];
+        for &(left, right) in BEAMS.iter() {
+            for &(width, height) in ships.iter() {
+                let mut tracker = EdgeTracker::new(LineOracle::new(left, right));
+                let mut estimator = Estimator::new(LineOracle::new(left, right));
+                assert_eq!(
+                    estimator.fit(width, height).unwrap(),
+                    tracker.fit(width, height).unwrap(),
+                    "{}x{} box in beam {:?} {:?}",
+                    width,
+                    height,
+                    left,
+                    right
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_slopes() {
+        let mut estimator = Estimator::new(LineOracle::new((1, 2), (3, 4)));
+        let mut tracker = EdgeTracker::new(LineOracle::new((1, 2), (3, 4)));
+        assert_eq!(
+            estimator.fit(100, 100).unwrap(),
+            tracker.fit(100, 100).unwrap()
+        );
+
+        let (left, right) = estimator.slopes().unwrap();
+        assert!((left - 0.5).abs() < 0.01);
+        assert!((right - 0.75).abs() < 0.01);
+
+        // far fewer queries than following the beam row by row
+        assert!(estimator.oracle().calls() * 5 < tracker.oracle().calls());
+    }
+}
@@ -1,34 +1,28 @@
+mod beam;
 mod emulator;
-use self::emulator::{Emulator, Program, Status};
-
-fn check_pos(program: Program, x: usize, y: usize) -> Result<bool, &'static str> {
-    let mut emu = Emulator::new(program);
-    emu.add_input(x as i64);
-    emu.add_input(y as i64);
-    match emu.run()? {
-        Status::Output(1) => Ok(true),
-        Status::Output(0) => Ok(false),
-        _ => Err("unexpected emulator output"),
-    }
-}
+mod estimate;
+mod tracker;
+use self::beam::{Oracle, ProgramOracle, SnapshotOracle};
+use self::emulator::Program;
+use self::estimate::Estimator;
+use self::tracker::EdgeTracker;
+use std::env;
+
+const MAX_ROWS: usize = 100_000;
 
 fn gen_image(
-    program: Program,
+    oracle: &mut impl Oracle,
     x: usize,
     y: usize,
     width: usize,
     height: usize,
 ) -> Result<Vec<bool>, &'static str> {
-    let mut res = Vec::with_capacity(width * height);
-    for y in y..y + height {
-        for x in x..x + width {
-            res.push(check_pos(program.clone(), x, y)?);
-        }
-    }
-    Ok(res)
+    let positions: Vec<(usize, usize)> = (y..y + height)
+        .flat_map(|y| (x..x + width).map(move |x| (x, y)))
+        .collect();
+    oracle.query_batch(&positions)
 }
 
-#[allow(dead_code)]
 fn print_image(image: &[bool], width: usize, height: usize) {
     for y in 0..height {
         for x in 0..width {
@@ -38,10 +32,15 @@ fn print_image(image: &[bool], width: usize, height: usize) {
     }
 }
 
-fn ship_fits(program: Program, x: usize, y: usize, size: usize) -> Result<bool, &'static str> {
-    for y in (y + 1 - size)..=y {
-        for x in x..x + size {
-            if !check_pos(program.clone(), x, y)? {
+fn ship_fits(
+    oracle: &mut impl Oracle,
+    x: usize,
+    y: usize,
+    (width, height): (usize, usize),
+) -> Result<bool, &'static str> {
+    for y in (y + 1 - height)..=y {
+        for x in x..x + width {
+            if !oracle.query(x, y)? {
                 return Ok(false);
             }
         }
@@ -50,35 +49,198 @@ fn ship_fits(program: Program, x: usize, y: usize, size: usize) -> Result<bool,
     Ok(true)
 }
 
-// returns bottom left corner
+// returns bottom left corner; rows the beam misses are skipped, and the beam's left edge is looked
+// for no further right than max_step cells per row
 fn find_pos_for_ship(
-    program: Program,
+    oracle: &mut impl Oracle,
     mut x: usize,
     mut y: usize,
-    size: usize,
+    ship: (usize, usize),
+    max_step: usize,
 ) -> Result<(usize, usize), &'static str> {
-    while !(ship_fits(program.clone(), x, y, size)?) {
+    loop {
+        let mut left = None;
+        for cx in x..=max_step * (y + 1) {
+            if oracle.query(cx, y)? {
+                left = Some(cx);
+                break;
+            }
+        }
+        if let Some(left) = left {
+            x = left;
+            if ship_fits(oracle, x, y, ship)? {
+                return Ok((x, y));
+            }
+        }
+
         y += 1;
-        while !check_pos(program.clone(), x, y)? {
-            x += 1;
+        if y >= MAX_ROWS {
+            return Err("ship does not fit in the beam");
         }
     }
+}
 
-    Ok((x, y))
+fn print_calls(oracle: &impl Oracle) {
+    println!(
+        "drone deployments = {} ({} more answered from cache)",
+        oracle.calls(),
+        oracle.hits()
+    );
 }
 
-fn main() {
-    let input = include_str!("input.txt");
-    let program = Program::new(input).expect("failed to parse program");
+#[derive(Clone, Copy)]
+enum Solver {
+    Naive,
+    Tracker,
+    Estimator,
+}
+
+struct Options {
+    print: bool,
+    max_step: usize,
+    // (width, height)
+    ship: (usize, usize),
+}
 
-    let image = gen_image(program.clone(), 0, 0, 50, 50).expect("failed to generate image");
+// checks every cell, as a baseline for the edge tracker
+fn solve_naive(oracle: &mut impl Oracle, options: &Options) {
+    let image = gen_image(oracle, 0, 0, 50, 50).expect("failed to generate image");
+    if options.print {
+        print_image(&image, 50, 50);
+    }
     let points = image.iter().filter(|&&p| p).count();
     println!("Part 1: points affected by beam = {}", points);
 
-    let size = 100;
-    let (x, y) = find_pos_for_ship(program, 0, 99, size).expect("failed to find position for ship");
-    let y = y - size + 1; // use top-left corner
+    let height = options.ship.1;
+    let (x, y) = find_pos_for_ship(oracle, 0, height - 1, options.ship, options.max_step)
+        .expect("failed to find position for ship");
+    let y = y + 1 - height; // use top-left corner
+
+    let res = x * 10_000 + y;
+    println!("Part 2: result = {}", res);
+    print_calls(oracle);
+}
+
+fn solve_tracked(oracle: impl Oracle, options: &Options) {
+    let mut tracker = EdgeTracker::new(oracle).with_max_step(options.max_step);
+    if options.print {
+        let mut image = Vec::with_capacity(50 * 50);
+        for y in 0..50 {
+            for x in 0..50 {
+                image.push(tracker.contains(x, y).expect("failed to scan beam"));
+            }
+        }
+        print_image(&image, 50, 50);
+    }
+
+    let points = tracker.count(50, 50).expect("failed to scan beam");
+    println!("Part 1: points affected by beam = {}", points);
+
+    let (width, height) = options.ship;
+    let (x, y) = tracker
+        .fit(width, height)
+        .expect("failed to find position for ship");
+    let res = x * 10_000 + y;
+    println!("Part 2: result = {}", res);
+    print_calls(tracker.oracle());
+}
+
+fn solve_estimated(mut oracle: impl Oracle, options: &Options) {
+    let image = gen_image(&mut oracle, 0, 0, 50, 50).expect("failed to generate image");
+    if options.print {
+        print_image(&image, 50, 50);
+    }
+    let points = image.iter().filter(|&&p| p).count();
+    println!("Part 1: points affected by beam = {}", points);
 
+    let mut estimator = Estimator::new(oracle).with_max_step(options.max_step);
+    let (width, height) = options.ship;
+    let (x, y) = estimator
+        .fit(width, height)
+        .expect("failed to find position for ship");
     let res = x * 10_000 + y;
     println!("Part 2: result = {}", res);
+    if let Some((left, right)) = estimator.slopes() {
+        println!("beam slopes = {:.4}, {:.4}", left, right);
+    }
+    print_calls(estimator.oracle());
+}
+
+fn solve(mut oracle: impl Oracle, solver: Solver, options: &Options) {
+    match solver {
+        Solver::Naive => solve_naive(&mut oracle, options),
+        Solver::Tracker => solve_tracked(oracle, options),
+        Solver::Estimator => solve_estimated(oracle, options),
+    }
+}
+
+fn main() {
+    let usage = "usage: day19 [--naive | --estimate] [--ship <width>x<height>] [--print] \
+                 [--max-step <n>] [--fresh] [--threads <n>]";
+    let mut solver = Solver::Tracker;
+    let mut options = Options {
+        print: false,
+        max_step: 10,
+        ship: (100, 100),
+    };
+    let mut fresh = false;
+    let mut threads = 1;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut number = || -> usize { args.next().and_then(|n| n.parse().ok()).expect(usage) };
+        match arg.as_str() {
+            "--naive" => solver = Solver::Naive,
+            "--estimate" => solver = Solver::Estimator,
+            "--print" => options.print = true,
+            "--max-step" => options.max_step = number(),
+            // deploy a fresh drone for every query instead of reusing a snapshot
+            "--fresh" => fresh = true,
+            "--threads" => threads = number(),
+            "--ship" => {
+                options.ship = args
+                    .next()
+                    .and_then(|ship| {
+                        let (width, height) = ship.split_once('x')?;
+                        Some((width.parse().ok()?, height.parse().ok()?))
+                    })
+                    .filter(|&(width, height)| width > 0 && height > 0)
+                    .expect(usage)
+            }
+            _ => panic!("{}", usage),
+        }
+    }
+
+    let input = include_str!("input.txt");
+    let program = Program::new(input).expect("failed to parse program");
+
+    if fresh {
+        solve(ProgramOracle::new(program), solver, &options);
+    } else {
+        let oracle = SnapshotOracle::new(program)
+            .expect("failed to start drone program")
+            .with_threads(threads);
+        solve(oracle, solver, &options);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::find_pos_for_ship;
+    use crate::beam::LineOracle;
+    use crate::tracker::EdgeTracker;
+
+    #[test]
+    fn test_find_pos_for_ship() {
+        // narrow, so that rows near the origin are empty
+        for &(left, right) in [((7, 10), (8, 10)), ((1, 2), (3, 4))].iter() {
+            for &(width, height) in [(10, 3), (3, 10), (1, 1), (20, 20)].iter() {
+                let mut oracle = LineOracle::new(left, right);
+                let (x, y) =
+                    find_pos_for_ship(&mut oracle, 0, height - 1, (width, height), 10).unwrap();
+                let mut tracker = EdgeTracker::new(LineOracle::new(left, right));
+                assert_eq!((x, y + 1 - height), tracker.fit(width, height).unwrap());
+            }
+        }
+    }
 }
@@ -0,0 +1,203 @@
+// beam model that only follows the left and right edge of each row
+//
+// Both edges of the beam move right (or stay) from one row to the next, so each row is found by
+// stepping on from the edges of the previous row, with a handful of queries per row.
+
+use crate::beam::Oracle;
+
+pub struct EdgeTracker<O> {
+    oracle: O,
+    // inclusive (left, right) edges of each row tracked so far, None for rows the beam misses
+    rows: Vec<Option<(usize, usize)>>,
+    // (y, left, right) of the last row the beam hits
+    last: Option<(usize, usize, usize)>,
+    // furthest the beam's right edge moves per row
+    max_step: usize,
+    max_rows: usize,
+}
+
+impl<O: Oracle> EdgeTracker<O> {
+    pub fn new(oracle: O) -> Self {
+        EdgeTracker {
+            oracle,
+            rows: Vec::new(),
+            last: None,
+            max_step: 10,
+            max_rows: 100_000,
+        }
+    }
+
+    pub fn with_max_step(mut self, max_step: usize) -> Self {
+        self.max_step = max_step;
+        self
+    }
+
+    pub fn oracle(&self) -> &O {
+        &self.oracle
+    }
+
+    // returns the inclusive (left, right) edges of row y, or None if the beam misses it
+    pub fn edges(&mut self, y: usize) -> Result<Option<(usize, usize)>, &'static str> {
+        if y >= self.max_rows {
+            return Err("row out of range");
+        }
+        while self.rows.len() <= y {
+            self.track_next_row()?;
+        }
+        Ok(self.rows[y])
+    }
+
+    pub fn contains(&mut self, x: usize, y: usize) -> Result<bool, &'static str> {
+        Ok(self
+            .edges(y)?
+            .is_some_and(|(left, right)| left <= x && x <= right))
+    }
+
+    fn track_next_row(&mut self) -> Result<(), &'static str> {
+        let y = self.rows.len();
+        let (from, to) = match self.last {
+            Some((last_y, left, right)) => (left, right + self.max_step * (y - last_y)),
+            None => (0, self.max_step * (y + 1)),
+        };
+
+        let mut left = None;
+        for x in from..=to {
+            if self.oracle.query(x, y)? {
+                left = Some(x);
+                break;
+            }
+        }
+        let left = match left {
+            Some(left) => left,
+            None => {
+                self.rows.push(None);
+                return Ok(());
+            }
+        };
+
+        // start from the previous right edge, which is still in the beam when the beam widens
+        let mut right = left;
+        if let Some((_, _, last_right)) = self.last {
+            if last_right > left && self.oracle.query(last_right, y)? {
+                right = last_right;
+            }
+        }
+        while self.oracle.query(right + 1, y)? {
+            right += 1;
+        }
+
+        self.rows.push(Some((left, right)));
+        self.last = Some((y, left, right));
+        Ok(())
+    }
+
+    // returns the number of points in the beam with x < width and y < height
+    pub fn count(&mut self, width: usize, height: usize) -> Result<usize, &'static str> {
+        let mut res = 0;
+        for y in 0..height {
+            if let Some((left, right)) = self.edges(y)? {
+                if left < width {
+                    res += right.min(width - 1) - left + 1;
+                }
+            }
+        }
+        Ok(res)
+    }
+
+    // returns the top left corner of the nearest width x height box that fits in the beam
+    pub fn fit(&mut self, width: usize, height: usize) -> Result<(usize, usize), &'static str> {
+        for bottom in height - 1..self.max_rows {
+            let top = bottom + 1 - height;
+            let (mut left, mut right) = (0, usize::MAX);
+            for y in top..=bottom {
+                match self.edges(y)? {
+                    Some((l, r)) => {
+                        left = left.max(l);
+                        right = right.min(r);
+                    }
+                    None => {
+                        right = 0;
+                        left = 1;
+                        break;
+                    }
+                }
+            }
+            if left <= right && right - left + 1 >= width {
+                return Ok((left, top));
+            }
+        }
+        Err("box does not fit in the beam")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EdgeTracker;
+    use crate::beam::{LineOracle, Oracle};
+
+    const BEAMS: [((usize, usize), (usize, usize)); 3] = [
+        ((1, 2), (3, 4)),
+        // narrow, so that rows near the origin are empty
+        ((7, 10), (8, 10)),
+        ((13, 10), (21, 10)),
+    ];
+
+    #[test]
+    fn test_edges() {
+        for &(left, right) in BEAMS.iter() {
+            let beam = LineOracle::new(left, right);
+            let mut tracker = EdgeTracker::new(LineOracle::new(left, right));
+            for y in 0..200 {
+                for x in 0..400 {
+                    assert_eq!(tracker.contains(x, y).unwrap(), beam.contains(x, y));
+                }
+            }
+
+            // a few queries per row, rather than one per cell
+            assert!(tracker.oracle().calls() < 200 * 8);
+        }
+    }
+
+    #[test]
+    fn test_steep_beam() {
+        let beam = LineOracle::new((30, 1), (40, 1));
+        let mut tracker = EdgeTracker::new(LineOracle::new((30, 1), (40, 1))).with_max_step(40);
+        for y in 0..50 {
+            for x in 0..2100 {
+                assert_eq!(tracker.contains(x, y).unwrap(), beam.contains(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn test_fit() {
+        for &(left, right) in BEAMS.iter() {
+            let beam = LineOracle::new(left, right);
+            for &(width, height) in [(1, 1), (10, 10), (5, 12), (12, 5)].iter() {
+                let mut tracker = EdgeTracker::new(LineOracle::new(left, right));
+                let fit = tracker.fit(width, height).unwrap();
+
+                // the first fit by rows, then columns
+                let fits = |x: usize, y: usize| {
+                    (y..y + height).all(|y| (x..x + width).all(|x| beam.contains(x, y)))
+                };
+                let expected = (0..)
+                    .flat_map(|y| (0..4 * (y + height)).map(move |x| (x, y)))
+                    .find(|&(x, y)| fits(x, y))
+                    .unwrap();
+                assert_eq!(fit, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_count() {
+        let beam = LineOracle::new((1, 2), (3, 4));
+        let mut tracker = EdgeTracker::new(LineOracle::new((1, 2), (3, 4)));
+        let expected = (0..50)
+            .flat_map(|y| (0..50).map(move |x| (x, y)))
+            .filter(|&(x, y)| beam.contains(x, y))
+            .count();
+        assert_eq!(tracker.count(50, 50).unwrap(), expected);
+    }
+}
@@ -0,0 +1,201 @@
+// text based I/O for ASCII capable intcode programs
+
+use crate::emulator::{Emulator, Program, Status};
+use std::collections::VecDeque;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Event {
+    Line(String),
+    // a value outside the ASCII range, such as a puzzle answer
+    NonAscii(i64),
+    NeedsInput,
+    Halted,
+}
+
+#[derive(Clone)]
+pub struct AsciiComputer {
+    emu: Emulator,
+    line: String,
+    pending: VecDeque<Event>,
+}
+
+impl AsciiComputer {
+    pub fn new(program: Program) -> Self {
+        AsciiComputer::with_emulator(Emulator::new(program))
+    }
+
+    // for programs that need patching before they run
+    pub fn with_emulator(emu: Emulator) -> Self {
+        AsciiComputer {
+            emu,
+            line: String::new(),
+            pending: VecDeque::new(),
+        }
+    }
+
+    pub fn send_line(&mut self, line: &str) {
+        for b in line.bytes().chain(Some(b'\n')) {
+            self.emu.add_input(b as i64);
+        }
+    }
+
+    // returns Ok(None) if the emulator reaches `deadline` instructions without an event
+    fn next_event_before(&mut self, deadline: Option<u64>) -> Result<Option<Event>, &'static str> {
+        if let Some(event) = self.pending.pop_front() {
+            return Ok(Some(event));
+        }
+
+        loop {
+            let status = match deadline {
+                None => self.emu.run()?,
+                Some(deadline) => {
+                    let remaining = deadline.saturating_sub(self.emu.instructions());
+                    match self.emu.run_for(remaining)? {
+                        Some(status) => status,
+                        None => return Ok(None),
+                    }
+                }
+            };
+            let event = match status {
+                Status::Output(out) if (0..128).contains(&out) => {
+                    if out == b'\n' as i64 {
+                        return Ok(Some(Event::Line(std::mem::take(&mut self.line))));
+                    }
+                    self.line.push(out as u8 as char);
+                    continue;
+                }
+                Status::Output(out) => Event::NonAscii(out),
+                Status::NeedsInput => Event::NeedsInput,
+                Status::Halted => Event::Halted,
+            };
+
+            if self.line.is_empty() {
+                return Ok(Some(event));
+            }
+            self.pending.push_back(event);
+            return Ok(Some(Event::Line(std::mem::take(&mut self.line))));
+        }
+    }
+
+    // text without a trailing newline is returned as a line before any other event
+    pub fn next_event(&mut self) -> Result<Event, &'static str> {
+        Ok(self.next_event_before(None)?.unwrap())
+    }
+
+    // returns every event up to and including the next NeedsInput or Halted
+    pub fn read_until_input(&mut self) -> Result<Vec<Event>, &'static str> {
+        // no program runs for u64::MAX instructions
+        Ok(self.read_until_input_within(u64::MAX)?.unwrap())
+    }
+
+    // like read_until_input, but returns Ok(None) if the program runs for more than
+    // `max_instructions` first
+    pub fn read_until_input_within(
+        &mut self,
+        max_instructions: u64,
+    ) -> Result<Option<Vec<Event>>, &'static str> {
+        let deadline = self.emu.instructions().saturating_add(max_instructions);
+        let mut res = Vec::new();
+        loop {
+            let event = match self.next_event_before(Some(deadline))? {
+                Some(event) => event,
+                None => {
+                    // keep what was read for the next call
+                    self.pending.extend(res);
+                    return Ok(None);
+                }
+            };
+            let done = event == Event::NeedsInput || event == Event::Halted;
+            res.push(event);
+            if done {
+                return Ok(Some(res));
+            }
+        }
+    }
+
+    // returns every event up to and including the first line containing `prompt`
+    pub fn wait_for(&mut self, prompt: &str) -> Result<Vec<Event>, &'static str> {
+        let mut res = Vec::new();
+        loop {
+            let event = self.next_event()?;
+            match &event {
+                Event::Line(line) if line.contains(prompt) => {
+                    res.push(event);
+                    return Ok(res);
+                }
+                Event::NeedsInput => return Err("program needs input before prompt"),
+                Event::Halted => return Err("program halted before prompt"),
+                _ => res.push(event),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AsciiComputer, Event};
+    use crate::emulator::Program;
+
+    // prints "hi", 300 and "ab" without a newline, reads a value, then prints "x" and halts
+    fn program() -> AsciiComputer {
+        let code = "104,104,104,105,104,10,104,300,104,97,104,98,3,50,104,120,99";
+        AsciiComputer::new(Program::new(code).unwrap())
+    }
+
+    fn line(s: &str) -> Event {
+        Event::Line(s.to_string())
+    }
+
+    #[test]
+    fn test_events() {
+        let mut computer = program();
+        assert_eq!(
+            computer.read_until_input().unwrap(),
+            vec![
+                line("hi"),
+                Event::NonAscii(300),
+                line("ab"),
+                Event::NeedsInput
+            ]
+        );
+
+        // the unfinished line comes out before the program halts
+        computer.send_line("");
+        assert_eq!(
+            computer.read_until_input().unwrap(),
+            vec![line("x"), Event::Halted]
+        );
+    }
+
+    #[test]
+    fn test_wait_for() {
+        let mut computer = program();
+        assert_eq!(computer.wait_for("i").unwrap(), vec![line("hi")]);
+        assert_eq!(
+            computer.wait_for("x"),
+            Err("program needs input before prompt")
+        );
+        computer.send_line("");
+        assert_eq!(computer.wait_for("y"), Err("program halted before prompt"));
+    }
+
+    #[test]
+    fn test_read_within() {
+        let expected = vec![
+            line("hi"),
+            Event::NonAscii(300),
+            line("ab"),
+            Event::NeedsInput,
+        ];
+        // stopping partway through "hi", and after "hi" and 300 have come out
+        for &budget in [2, 4].iter() {
+            let mut computer = program();
+            assert_eq!(computer.read_until_input_within(budget).unwrap(), None);
+            // the events so far are kept for the next read
+            assert_eq!(
+                computer.read_until_input_within(100).unwrap(),
+                Some(expected.clone())
+            );
+        }
+    }
+}
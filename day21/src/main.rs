@@ -1,32 +1,91 @@
+mod ascii;
 mod emulator;
+mod springscript;
 mod springscript_sim;
-use crate::emulator::{Emulator, Program, Status};
-use std::io;
-use std::io::Write;
+mod synth;
+use crate::ascii::{AsciiComputer, Event};
+use crate::emulator::Program;
+use crate::springscript::{Mode, Script};
+use std::{env, fs};
+
+fn print_event(event: Event) {
+    match event {
+        Event::Halted => {}
+        Event::Line(line) => println!("{}", line),
+        Event::NonAscii(out) => println!("Non-ASCII Output: {}", out),
+        Event::NeedsInput => panic!("input is short"),
+    }
+}
 
 fn run_ascii_program_with_input(program: Program, input: &str) {
-    let mut emu = Emulator::new(program);
-    emu.add_inputs(input.bytes().map(|b| b as i64));
-    loop {
-        let status = emu.run().expect("emulator error");
-        match status {
-            Status::Halted => return,
-            Status::Output(out) => {
-                if out < 255 {
-                    io::stdout().write_all(&[out as u8]).expect("write failed");
-                } else {
-                    println!("Non-ASCII Output: {}", out);
-                }
-            }
-            Status::NeedsInput => panic!("input is short"),
-        }
+    let mut computer = AsciiComputer::new(program);
+    let intro = computer
+        .wait_for("Input instructions:")
+        .expect("springdroid did not ask for instructions");
+    intro.into_iter().for_each(print_event);
+
+    for line in input.lines() {
+        computer.send_line(line);
     }
+    let events = computer.read_until_input().expect("emulator error");
+    events.into_iter().for_each(print_event);
 }
 
 fn parse_test(test: &str) -> Vec<bool> {
     test.bytes().map(|b| b == b'#').collect()
 }
 
+// hulls that programs found by the searcher failed on
+const WALK_TESTS: [&str; 4] = [
+    "####.####",
+    "#####..#.####",
+    "#####...####",
+    "#####.#..####",
+];
+const RUN_TESTS: [&str; 11] = [
+    "#.#",
+    "#####..#.#",
+    "####...#",
+    "####.#..#",
+    "####.#.##.#.#",
+    "####.###.#..#",
+    "####..#..##.#",
+    "#####.###.#..#",
+    "####.###..#..#",
+    "#####.#.#.#.#.###",
+    "#####.####.#..###",
+];
+
+fn tests_for(mode: Mode) -> Vec<Vec<bool>> {
+    let tests: &[&str] = match mode {
+        Mode::Walk => &WALK_TESTS,
+        Mode::Run => &RUN_TESTS,
+    };
+    tests.iter().map(|test| parse_test(test)).collect()
+}
+
+// parses and validates a springscript program, and tries it on the known hulls
+fn check_script(text: &str) -> Option<Script> {
+    let script = match springscript::parse(text).and_then(|script| {
+        script.validate()?;
+        Ok(script)
+    }) {
+        Ok(script) => script,
+        Err(err) => {
+            println!("error: {}", err);
+            return None;
+        }
+    };
+    print!("{}", script);
+    for test in tests_for(script.mode) {
+        if !springscript_sim::test_program(&script.instructions, &test) {
+            let hull: String = test.iter().map(|&b| if b { '#' } else { '.' }).collect();
+            println!("falls in hull {}", hull);
+        }
+    }
+    Some(script)
+}
+
 // I added tests here when the searcher would find a springscript program that would fail on the
 // real input. I used this method, with a springscript simulator, because simulating the
 // springscript programs is a lot faster than testing them with the input intcode and an intcode
@@ -34,40 +93,59 @@ fn parse_test(test: &str) -> Vec<bool> {
 #[allow(dead_code)]
 fn find_programs(program: Program) {
     // part 1
-    let tests = [
-        parse_test("####.####"),
-        parse_test("#####..#.####"),
-        parse_test("#####...####"),
-        parse_test("#####.#..####"),
-    ];
+    let tests = tests_for(Mode::Walk);
     let springscript = springscript_sim::find_program(true, &tests) + "\nWALK\n";
     println!("Program:\n{}", springscript);
     run_ascii_program_with_input(program.clone(), &(springscript));
 
     // part 2
-    let tests = [
-        parse_test("#.#"),
-        parse_test("#####..#.#"),
-        parse_test("####...#"),
-        parse_test("####.#..#"),
-        parse_test("####.#.##.#.#"),
-        parse_test("####.###.#..#"),
-        parse_test("####..#..##.#"),
-        parse_test("#####.###.#..#"),
-        parse_test("####.###..#..#"),
-        parse_test("#####.#.#.#.#.###"),
-        parse_test("#####.####.#..###"),
-    ];
+    let tests = tests_for(Mode::Run);
     let springscript = springscript_sim::find_program(false, &tests) + "\nRUN\n";
     println!("Program:\n{}", springscript);
     run_ascii_program_with_input(program, &(springscript));
 }
 
+// builds the programs from the decisions the tests require, which is much faster than searching
+fn synthesize_programs(program: Program) {
+    for &(mode, tests) in [(Mode::Walk, &WALK_TESTS[..]), (Mode::Run, &RUN_TESTS[..])].iter() {
+        let tests: Vec<Vec<bool>> = tests.iter().map(|test| parse_test(test)).collect();
+        let script = synth::find_program(mode, &tests).expect("could not find valid program");
+        println!("Program:\n{}", script);
+        run_ascii_program_with_input(program.clone(), &script.to_string());
+    }
+}
+
 fn main() {
-    let input = include_str!("input.txt");
-    let program = Program::new(input).expect("failed to parse program");
+    let usage = "usage: day21 [--check <file> | --script <file> | --synthesize]";
+    let mut args = env::args().skip(1);
+    let load = || {
+        let input = include_str!("input.txt");
+        Program::new(input).expect("failed to parse program")
+    };
+    let (check, send) = match args.next().as_deref() {
+        None => (None, false),
+        Some("--check") => (Some(args.next().expect(usage)), false),
+        // checks the script, then sends it to the springdroid
+        Some("--script") => (Some(args.next().expect(usage)), true),
+        Some("--synthesize") => {
+            synthesize_programs(load());
+            return;
+        }
+        _ => panic!("{}", usage),
+    };
+
+    if let Some(path) = check {
+        let text = fs::read_to_string(path).expect("failed to read script");
+        if let Some(script) = check_script(&text) {
+            if send {
+                run_ascii_program_with_input(load(), &script.to_string());
+            }
+        }
+        return;
+    }
 
     // programs were generated automatically, but this is slow for part 2
+    let program = load();
     println!("Part 1:\n------");
     let part1_input = "NOT D T
 OR C T
@@ -75,7 +153,7 @@ AND A T
 NOT T J
 WALK
 ";
-    run_ascii_program_with_input(program.clone(), &part1_input);
+    run_ascii_program_with_input(program.clone(), part1_input);
     println!("\nPart 2:\n------");
     let part2_input = "NOT H T
 OR C T
@@ -85,5 +163,5 @@ NOT T J
 AND D J
 RUN
 ";
-    run_ascii_program_with_input(program, &part2_input);
+    run_ascii_program_with_input(program, part2_input);
 }
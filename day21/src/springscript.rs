@@ -0,0 +1,254 @@
+// reading, checking and printing springscript programs
+
+use crate::springscript_sim::{IORegister, Instruction, Operation, Register, INPUT_REGISTERS};
+use std::fmt::{self, Display, Formatter};
+
+// the springdroid's memory only holds this many instructions
+pub const MAX_INSTRUCTIONS: usize = 15;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Mode {
+    // only sensors A to D are available
+    Walk,
+    Run,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct Script {
+    pub instructions: Vec<Instruction>,
+    pub mode: Mode,
+    // 1-based (line, column) of each instruction in the text it was read from
+    pub positions: Vec<(usize, usize)>,
+}
+
+// 1-based position of the problem in the script's text
+#[derive(Debug, Eq, PartialEq)]
+pub struct ScriptError {
+    pub line: usize,
+    pub col: usize,
+    pub msg: &'static str,
+}
+
+impl Display for ScriptError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.col, self.msg)
+    }
+}
+
+fn error(line: usize, col: usize, msg: &'static str) -> ScriptError {
+    ScriptError { line, col, msg }
+}
+
+// returns each word of the line with its 1-based column
+fn words(line: &str) -> Vec<(usize, &str)> {
+    let mut res = Vec::new();
+    let mut start = None;
+    for (i, c) in line.char_indices().chain(Some((line.len(), ' '))) {
+        match (start, c.is_whitespace()) {
+            (None, false) => start = Some(i),
+            (Some(s), true) => {
+                res.push((s + 1, &line[s..i]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    res
+}
+
+fn parse_register(word: &str) -> Option<Register> {
+    match word {
+        "T" => Some(Register::IO(IORegister::T)),
+        "J" => Some(Register::IO(IORegister::J)),
+        _ if word.len() == 1 => {
+            let c = word.as_bytes()[0];
+            INPUT_REGISTERS
+                .get(c.wrapping_sub(b'A') as usize)
+                .map(|&ir| Register::I(ir))
+        }
+        _ => None,
+    }
+}
+
+// parses the instructions and the final WALK or RUN command, ignoring blank lines
+pub fn parse(text: &str) -> Result<Script, ScriptError> {
+    let mut instructions = Vec::new();
+    let mut positions = Vec::new();
+    let mut mode = None;
+    let mut last = (0, 1);
+
+    for (i, line) in text.lines().enumerate() {
+        let n = i + 1;
+        let words = words(line);
+        let (col, first) = match words.first() {
+            Some(&word) => word,
+            None => continue,
+        };
+        if mode.is_some() {
+            return Err(error(n, col, "instruction after WALK or RUN"));
+        }
+        last = (n, line.len() + 1);
+
+        let op = match first {
+            "WALK" | "RUN" => {
+                if let Some(&(col, _)) = words.get(1) {
+                    return Err(error(n, col, "unexpected word"));
+                }
+                mode = Some(if first == "WALK" {
+                    Mode::Walk
+                } else {
+                    Mode::Run
+                });
+                continue;
+            }
+            "NOT" => Operation::NOT,
+            "AND" => Operation::AND,
+            "OR" => Operation::OR,
+            _ => return Err(error(n, col, "unknown instruction")),
+        };
+
+        let mut registers = [Register::IO(IORegister::T); 2];
+        for (j, register) in registers.iter_mut().enumerate() {
+            let (col, word) = *words
+                .get(j + 1)
+                .ok_or_else(|| error(n, line.len() + 1, "missing register"))?;
+            *register = parse_register(word).ok_or_else(|| error(n, col, "unknown register"))?;
+        }
+        if let Some(&(col, _)) = words.get(3) {
+            return Err(error(n, col, "unexpected word"));
+        }
+        let r2 = match registers[1] {
+            Register::IO(r2) => r2,
+            Register::I(_) => return Err(error(n, words[2].0, "only T and J can be written")),
+        };
+
+        instructions.push(Instruction {
+            op,
+            r1: registers[0],
+            r2,
+        });
+        positions.push((n, col));
+    }
+
+    match mode {
+        Some(mode) => Ok(Script {
+            instructions,
+            mode,
+            positions,
+        }),
+        None => Err(error(last.0, last.1, "missing WALK or RUN")),
+    }
+}
+
+impl Script {
+    // a script not read from any text, placed where it is printed
+    pub fn new(instructions: Vec<Instruction>, mode: Mode) -> Self {
+        let positions = (1..=instructions.len()).map(|line| (line, 1)).collect();
+        Script {
+            instructions,
+            mode,
+            positions,
+        }
+    }
+
+    // checks the script would be accepted by the springdroid
+    pub fn validate(&self) -> Result<(), ScriptError> {
+        if self.instructions.len() > MAX_INSTRUCTIONS {
+            let (line, col) = self.positions[MAX_INSTRUCTIONS];
+            return Err(error(line, col, "too many instructions"));
+        }
+        for (instr, &(line, col)) in self.instructions.iter().zip(self.positions.iter()) {
+            if let Register::I(ir) = instr.r1 {
+                if self.mode == Mode::Walk && ir as usize >= 4 {
+                    return Err(error(line, col, "only A to D can be read when walking"));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// one instruction per line, as the springdroid expects it
+impl Display for Script {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for instr in self.instructions.iter() {
+            writeln!(f, "{}", instr)?;
+        }
+        match self.mode {
+            Mode::Walk => writeln!(f, "WALK"),
+            Mode::Run => writeln!(f, "RUN"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, Mode, ScriptError};
+
+    fn parse_error(text: &str) -> (usize, usize, &'static str) {
+        let ScriptError { line, col, msg } = parse(text).unwrap_err();
+        (line, col, msg)
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let text = "NOT H T\nOR C T\nAND B T\nAND A T\nNOT T J\nAND D J\nRUN\n";
+        let script = parse(text).unwrap();
+        assert_eq!(script.instructions.len(), 6);
+        assert_eq!(script.mode, Mode::Run);
+        assert_eq!(script.to_string(), text);
+        assert_eq!(parse(&script.to_string()).unwrap(), script);
+
+        // spacing and blank lines are not kept
+        let script = parse("\n  NOT  D T\n\nOR C\tT\nWALK").unwrap();
+        assert_eq!(script.to_string(), "NOT D T\nOR C T\nWALK\n");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_error("NOT A J\nJUMP A J\nWALK"),
+            (2, 1, "unknown instruction")
+        );
+        assert_eq!(parse_error("AND A X\nWALK"), (1, 7, "unknown register"));
+        assert_eq!(parse_error("OR  A\nWALK"), (1, 6, "missing register"));
+        assert_eq!(parse_error("OR A J J\nWALK"), (1, 8, "unexpected word"));
+        assert_eq!(
+            parse_error("NOT J A\nRUN"),
+            (1, 7, "only T and J can be written")
+        );
+        assert_eq!(
+            parse_error("NOT A J\nNOT B T"),
+            (2, 8, "missing WALK or RUN")
+        );
+        assert_eq!(
+            parse_error("WALK\nNOT A J"),
+            (2, 1, "instruction after WALK or RUN")
+        );
+        assert_eq!(parse_error("RUN FAST"), (1, 5, "unexpected word"));
+    }
+
+    #[test]
+    fn test_validate() {
+        let walk = parse("NOT A J\nOR E J\nWALK").unwrap();
+        let err = walk.validate().unwrap_err();
+        assert_eq!((err.line, err.col), (2, 1));
+        assert_eq!(err.to_string(), "2:1: only A to D can be read when walking");
+        assert!(parse("NOT A J\nOR E J\nRUN").unwrap().validate().is_ok());
+
+        let long = "NOT A J\n".repeat(15) + "RUN";
+        assert!(parse(&long).unwrap().validate().is_ok());
+        let long = "NOT A J\n".repeat(16) + "RUN";
+        let err = parse(&long).unwrap().validate().unwrap_err();
+        assert_eq!((err.line, err.msg), (16, "too many instructions"));
+
+        // positions are in the text as written, not as printed
+        let walk = parse("NOT A J\n\n\n  OR E J\nWALK").unwrap();
+        assert_eq!(walk.positions, [(1, 1), (4, 3)]);
+        let err = walk.validate().unwrap_err();
+        assert_eq!((err.line, err.col), (4, 3));
+        let long = "\n".repeat(3) + &"NOT A J\n\n".repeat(16) + "RUN";
+        let err = parse(&long).unwrap().validate().unwrap_err();
+        assert_eq!((err.line, err.col), (34, 1));
+    }
+}
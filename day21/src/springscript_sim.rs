@@ -1,7 +1,9 @@
 // efficiently simulate springscript programs
 
+use std::fmt::{Display, Error, Formatter};
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
-enum InputRegister {
+pub enum InputRegister {
     A = 0,
     B = 1,
     C = 2,
@@ -12,7 +14,7 @@ enum InputRegister {
     H = 7,
     I = 8,
 }
-const INPUT_REGISTERS: [InputRegister; 9] = [
+pub const INPUT_REGISTERS: [InputRegister; 9] = [
     InputRegister::A,
     InputRegister::B,
     InputRegister::C,
@@ -25,28 +27,29 @@ const INPUT_REGISTERS: [InputRegister; 9] = [
 ];
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
-enum IORegister {
+pub enum IORegister {
     T = 0,
     J = 1,
 }
 const IO_REGISTERS: [IORegister; 2] = [IORegister::T, IORegister::J];
 
-#[derive(Copy, Clone, Eq, PartialEq)]
-enum Register {
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Register {
     I(InputRegister),
     IO(IORegister),
 }
-impl ToString for Register {
-    fn to_string(&self) -> String {
+impl Display for Register {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
         match self {
-            Register::I(i) => format!("{:?}", i),
-            Register::IO(io) => format!("{:?}", io),
+            Register::I(i) => write!(f, "{:?}", i),
+            Register::IO(io) => write!(f, "{:?}", io),
         }
     }
 }
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
-enum Operation {
+pub enum Operation {
     NOT,
     AND,
     OR,
@@ -54,19 +57,19 @@ enum Operation {
 
 const OPERATIONS: [Operation; 3] = [Operation::NOT, Operation::AND, Operation::OR];
 
-#[derive(Copy, Clone, Eq, PartialEq)]
-struct Instruction {
-    op: Operation,
-    r1: Register,
-    r2: IORegister,
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Instruction {
+    pub op: Operation,
+    pub r1: Register,
+    pub r2: IORegister,
 }
-impl ToString for Instruction {
-    fn to_string(&self) -> String {
-        format!("{:?} {} {:?}", self.op, self.r1.to_string(), self.r2)
+impl Display for Instruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(f, "{:?} {} {:?}", self.op, self.r1, self.r2)
     }
 }
 
-fn sim_program(program: &[Instruction], inputs: &[bool]) -> bool {
+pub fn sim_program(program: &[Instruction], inputs: &[bool]) -> bool {
     let mut io_registers = [false; 2];
     for instr in program {
         let input = match instr.r1 {
@@ -84,7 +87,7 @@ fn sim_program(program: &[Instruction], inputs: &[bool]) -> bool {
 }
 
 // returns true if successful
-fn test_program(program: &[Instruction], map: &[bool]) -> bool {
+pub fn test_program(program: &[Instruction], map: &[bool]) -> bool {
     let mut pos = 0;
     let mut map = Vec::from(map);
     map.extend(&[true, true, true, true, true, true, true, true, true]);
@@ -0,0 +1,411 @@
+// build springscript programs from the decisions the test hulls require
+//
+// Walking the droid over the hulls gives the sensor readings it has to jump or walk on. The jump
+// condition is then minimized as a sum of products with Quine-McCluskey, treating every reading
+// the hulls don't show as a don't care, and compiled to instructions over the T and J registers.
+
+use crate::springscript::{Mode, Script};
+use crate::springscript_sim::{IORegister, Instruction, Operation, Register, INPUT_REGISTERS};
+use std::collections::{HashMap, HashSet};
+
+// whether to jump for each sensor reading, with sensor A as bit 0
+pub type Decisions = HashMap<u16, bool>;
+
+// a product of the sensors set in `care`, each negated unless it is also set in `bits`
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Term {
+    pub bits: u16,
+    pub care: u16,
+}
+
+impl Term {
+    fn covers(&self, reading: u16) -> bool {
+        reading & self.care == self.bits
+    }
+
+    fn literals(&self) -> (Vec<Register>, Vec<Register>) {
+        let (mut positive, mut negative) = (Vec::new(), Vec::new());
+        for (i, &ir) in INPUT_REGISTERS.iter().enumerate() {
+            if self.care & 1 << i != 0 {
+                if self.bits & 1 << i != 0 {
+                    positive.push(Register::I(ir));
+                } else {
+                    negative.push(Register::I(ir));
+                }
+            }
+        }
+        (positive, negative)
+    }
+}
+
+fn sensors(mode: Mode) -> usize {
+    match mode {
+        Mode::Walk => 4,
+        Mode::Run => 9,
+    }
+}
+
+struct DecisionSearch<'a> {
+    tests: &'a [Vec<bool>],
+    sensors: usize,
+    decisions: Decisions,
+}
+
+impl DecisionSearch<'_> {
+    fn search<T>(
+        &mut self,
+        test: usize,
+        pos: usize,
+        f: &mut dyn FnMut(&Decisions) -> Option<T>,
+    ) -> Option<T> {
+        let map = match self.tests.get(test) {
+            Some(map) => map,
+            None => return f(&self.decisions),
+        };
+        // past the end of the hull, which is padded with ground for the sensors
+        if pos + 9 >= map.len() {
+            return self.search(test + 1, 0, f);
+        }
+        if !map[pos] {
+            return None;
+        }
+
+        let reading = (0..self.sensors)
+            .filter(|&i| map[pos + 1 + i])
+            .fold(0, |reading, i| reading | 1 << i);
+        let step = |jump| if jump { 4 } else { 1 };
+        if let Some(&jump) = self.decisions.get(&reading) {
+            return self.search(test, pos + step(jump), f);
+        }
+        for &jump in [false, true].iter() {
+            self.decisions.insert(reading, jump);
+            let res = self.search(test, pos + step(jump), f);
+            self.decisions.remove(&reading);
+            if res.is_some() {
+                return res;
+            }
+        }
+        None
+    }
+}
+
+// calls `f` with each set of decisions that gets the droid across every hull, stopping at the
+// first one it returns Some for
+pub fn find_decisions<T: AsRef<[bool]>, U>(
+    mode: Mode,
+    tests: &[T],
+    mut f: impl FnMut(&Decisions) -> Option<U>,
+) -> Option<U> {
+    let tests: Vec<Vec<bool>> = tests
+        .iter()
+        .map(|test| {
+            let mut map = Vec::from(test.as_ref());
+            map.extend(&[true; 9]);
+            map
+        })
+        .collect();
+    let mut search = DecisionSearch {
+        tests: &tests,
+        sensors: sensors(mode),
+        decisions: HashMap::new(),
+    };
+    search.search(0, 0, &mut f)
+}
+
+fn prime_implicants(sensors: usize, on: &[u16], off: &HashSet<u16>) -> Vec<Term> {
+    let all = (1 << sensors) - 1;
+    let mut terms: HashSet<Term> = (0..=all)
+        .filter(|reading| !off.contains(reading))
+        .map(|reading| Term {
+            bits: reading,
+            care: all,
+        })
+        .collect();
+
+    let mut primes = Vec::new();
+    while !terms.is_empty() {
+        let mut merged = HashSet::new();
+        let mut next = HashSet::new();
+        for &term in terms.iter() {
+            for i in 0..sensors {
+                let bit = 1 << i;
+                if term.care & bit == 0 {
+                    continue;
+                }
+                let other = Term {
+                    bits: term.bits ^ bit,
+                    care: term.care,
+                };
+                if terms.contains(&other) {
+                    merged.insert(term);
+                    next.insert(Term {
+                        bits: term.bits & !bit,
+                        care: term.care & !bit,
+                    });
+                }
+            }
+        }
+        primes.extend(terms.difference(&merged).cloned());
+        terms = next;
+    }
+
+    // only the implicants that cover a reading the droid has to jump on are of any use
+    primes.retain(|prime| on.iter().any(|&reading| prime.covers(reading)));
+    primes
+}
+
+// number of instructions to compute the term in T, which may hold an earlier value, and OR it
+// into J
+fn cost(term: &Term) -> usize {
+    let (positive, negative) = term.literals();
+    let negated = match negative.len() {
+        // loading the first sensor into T takes two
+        0 | 1 => 1,
+        n => n + 2,
+    };
+    negated + positive.len() + 1
+}
+
+struct Cover<'a> {
+    on: &'a [u16],
+    primes: &'a [Term],
+    chosen: Vec<Term>,
+    best: Option<(usize, Vec<Term>)>,
+}
+
+impl Cover<'_> {
+    fn search(&mut self, spent: usize) {
+        if self.best.as_ref().is_some_and(|&(best, _)| spent >= best) {
+            return;
+        }
+        let uncovered: Vec<u16> = self
+            .on
+            .iter()
+            .cloned()
+            .filter(|&reading| !self.chosen.iter().any(|term| term.covers(reading)))
+            .collect();
+        if uncovered.is_empty() {
+            self.best = Some((spent, self.chosen.clone()));
+            return;
+        }
+
+        // branch on the reading with the fewest ways to cover it, cheapest first
+        let options = |reading: u16| -> Vec<Term> {
+            let mut options: Vec<Term> = self
+                .primes
+                .iter()
+                .cloned()
+                .filter(|prime| prime.covers(reading))
+                .collect();
+            options.sort_by_key(cost);
+            options
+        };
+        let options = uncovered
+            .iter()
+            .map(|&reading| options(reading))
+            .min_by_key(|options| options.len())
+            .unwrap();
+        for term in options {
+            self.chosen.push(term);
+            self.search(spent + cost(&term));
+            self.chosen.pop();
+        }
+    }
+}
+
+// returns a cheapest sum of products that is true for the `on` readings and false for the `off`
+// ones
+pub fn minimize(sensors: usize, on: &[u16], off: &HashSet<u16>) -> Vec<Term> {
+    let primes = prime_implicants(sensors, on, off);
+    let mut cover = Cover {
+        on,
+        primes: &primes,
+        chosen: Vec::new(),
+        best: None,
+    };
+    cover.search(0);
+    cover.best.map(|(_, terms)| terms).unwrap_or_default()
+}
+
+fn instr(op: Operation, r1: Register, r2: IORegister) -> Instruction {
+    Instruction { op, r1, r2 }
+}
+
+// sets `reg` to the AND of the term's literals; `fresh` is whether it still holds false
+fn compile_term(term: &Term, reg: IORegister, fresh: bool, res: &mut Vec<Instruction>) {
+    let (positive, negative) = term.literals();
+    let load = |r1, res: &mut Vec<Instruction>| {
+        if fresh {
+            res.push(instr(Operation::OR, r1, reg));
+        } else {
+            res.push(instr(Operation::NOT, r1, reg));
+            res.push(instr(Operation::NOT, Register::IO(reg), reg));
+        }
+    };
+
+    let mut positive = positive.into_iter();
+    match negative.len() {
+        0 => load(positive.next().unwrap(), res),
+        1 => res.push(instr(Operation::NOT, negative[0], reg)),
+        _ => {
+            // NOT of the OR of the negated sensors
+            load(negative[0], res);
+            for &r1 in negative[1..].iter() {
+                res.push(instr(Operation::OR, r1, reg));
+            }
+            res.push(instr(Operation::NOT, Register::IO(reg), reg));
+        }
+    }
+    for r1 in positive {
+        res.push(instr(Operation::AND, r1, reg));
+    }
+}
+
+// compiles a sum of products into J, trying each pair of terms to compute first, while T and J
+// still hold false
+fn compile(terms: &[Term]) -> Vec<Instruction> {
+    if terms.iter().any(|term| term.care == 0) {
+        return vec![instr(
+            Operation::NOT,
+            Register::IO(IORegister::J),
+            IORegister::J,
+        )];
+    }
+
+    let mut best: Option<Vec<Instruction>> = None;
+    for first in 0..terms.len() {
+        for second in (0..terms.len()).filter(|&second| second != first || terms.len() == 1) {
+            let mut order = vec![first];
+            if second != first {
+                order.push(second);
+            }
+            order.extend((0..terms.len()).filter(|&i| i != first && i != second));
+
+            let mut res = Vec::new();
+            compile_term(&terms[order[0]], IORegister::J, true, &mut res);
+            for (n, &i) in order[1..].iter().enumerate() {
+                compile_term(&terms[i], IORegister::T, n == 0, &mut res);
+                res.push(instr(
+                    Operation::OR,
+                    Register::IO(IORegister::T),
+                    IORegister::J,
+                ));
+            }
+            if best.as_ref().is_none_or(|best| res.len() < best.len()) {
+                best = Some(res);
+            }
+        }
+    }
+    best.unwrap_or_default()
+}
+
+// returns the shortest program found for the decisions, computing either the jump condition or
+// its negation
+pub fn synthesize(mode: Mode, decisions: &Decisions) -> Script {
+    let readings = |jump: bool| -> Vec<u16> {
+        decisions
+            .iter()
+            .filter(|&(_, &j)| j == jump)
+            .map(|(&reading, _)| reading)
+            .collect()
+    };
+    let (jump, walk) = (readings(true), readings(false));
+    let sensors = sensors(mode);
+
+    let direct = compile(&minimize(sensors, &jump, &walk.iter().cloned().collect()));
+    let mut negated = compile(&minimize(sensors, &walk, &jump.iter().cloned().collect()));
+    negated.push(instr(
+        Operation::NOT,
+        Register::IO(IORegister::J),
+        IORegister::J,
+    ));
+
+    let instructions = if negated.len() < direct.len() {
+        negated
+    } else {
+        direct
+    };
+    Script::new(instructions, mode)
+}
+
+// returns the shortest program built from any of the decisions that get the droid across every
+// hull, if it fits in the springdroid
+pub fn find_program<T: AsRef<[bool]>>(mode: Mode, tests: &[T]) -> Option<Script> {
+    let mut best: Option<Script> = None;
+    find_decisions(mode, tests, |decisions| {
+        let script = synthesize(mode, decisions);
+        if best
+            .as_ref()
+            .is_none_or(|best| script.instructions.len() < best.instructions.len())
+        {
+            best = Some(script);
+        }
+        None::<()>
+    });
+    best.filter(|script| script.validate().is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_decisions, find_program, minimize, synthesize, Decisions, Term};
+    use crate::springscript::Mode;
+    use crate::springscript_sim::{sim_program, test_program};
+    use crate::{parse_test, RUN_TESTS, WALK_TESTS};
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_minimize() {
+        // jump for A and not B, with C and D as don't cares
+        let on = [0b0001, 0b0101, 0b1101];
+        let off: HashSet<u16> = [0b0000, 0b0010, 0b0011, 0b1010, 0b1111]
+            .iter()
+            .cloned()
+            .collect();
+        assert_eq!(
+            minimize(4, &on, &off),
+            vec![Term {
+                bits: 0b01,
+                care: 0b11
+            }]
+        );
+
+        // exclusive or needs both terms
+        let on = [0b01, 0b10];
+        let off: HashSet<u16> = [0b00, 0b11].iter().cloned().collect();
+        assert_eq!(minimize(2, &on, &off).len(), 2);
+    }
+
+    #[test]
+    fn test_synthesize() {
+        // jump when there is a hole in A, B or C and ground at D
+        let jump = |reading: u16| reading & 0b1000 != 0 && reading & 0b0111 != 0b0111;
+        let decisions: Decisions = (0..16).map(|reading| (reading, jump(reading))).collect();
+        let script = synthesize(Mode::Walk, &decisions);
+        assert!(script.validate().is_ok());
+        assert!(script.instructions.len() <= 6);
+        for reading in 0..16 {
+            let sensors: Vec<bool> = (0..9).map(|i| reading & 1 << i != 0).collect();
+            assert_eq!(sim_program(&script.instructions, &sensors), jump(reading));
+        }
+    }
+
+    #[test]
+    fn test_find_program() {
+        let cases = [
+            (Mode::Walk, &WALK_TESTS[..], 4),
+            (Mode::Run, &RUN_TESTS[..], 8),
+        ];
+        for &(mode, tests, len) in cases.iter() {
+            let tests: Vec<Vec<bool>> = tests.iter().map(|test| parse_test(test)).collect();
+            let script = find_program(mode, &tests).unwrap();
+            assert_eq!(script.instructions.len(), len);
+            assert!(tests
+                .iter()
+                .all(|test| test_program(&script.instructions, test)));
+        }
+
+        // a hull no droid can cross
+        let tests = [parse_test("#....#")];
+        assert!(find_decisions(Mode::Walk, &tests, |_| Some(())).is_none());
+    }
+}
@@ -0,0 +1,251 @@
+// packet capture and single node replay
+//
+// A capture file holds one event per line, with space separated fields. Blank lines and lines
+// starting with '#' are ignored. `tick` counts scheduler slices across the whole network, and
+// `poll` counts the input reads of a single node, starting from 1.
+//
+//   packet <tick> <src> <dest> <x> <y>    node <src> sent a packet to <dest>
+//   recv <tick> <addr> <poll> <x> <y>     node <addr> read a packet on its <poll>th input read
+//   idle <tick>                           the idle policy reported the network as idle
+//   inject <tick> <dest> <x> <y>          the NAT injected a packet into <dest>'s queue
+
+use crate::emulator::{Emulator, Program, Status};
+use crate::network::Packet;
+use std::fmt::{Display, Error, Formatter};
+use std::str::FromStr;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Event {
+    Packet {
+        tick: u64,
+        src: usize,
+        dest: i64,
+        packet: Packet,
+    },
+    Recv {
+        tick: u64,
+        addr: usize,
+        poll: u64,
+        packet: Packet,
+    },
+    Idle {
+        tick: u64,
+    },
+    Inject {
+        tick: u64,
+        dest: usize,
+        packet: Packet,
+    },
+}
+
+impl Display for Event {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            Event::Packet {
+                tick,
+                src,
+                dest,
+                packet,
+            } => write!(
+                f,
+                "packet {} {} {} {} {}",
+                tick, src, dest, packet.x, packet.y
+            ),
+            Event::Recv {
+                tick,
+                addr,
+                poll,
+                packet,
+            } => write!(
+                f,
+                "recv {} {} {} {} {}",
+                tick, addr, poll, packet.x, packet.y
+            ),
+            Event::Idle { tick } => write!(f, "idle {}", tick),
+            Event::Inject { tick, dest, packet } => {
+                write!(f, "inject {} {} {} {}", tick, dest, packet.x, packet.y)
+            }
+        }
+    }
+}
+
+impl FromStr for Event {
+    type Err = &'static str;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut fields = line.split_whitespace();
+        let kind = fields.next().ok_or("empty event")?;
+        let mut next = || -> Result<i64, &'static str> {
+            fields
+                .next()
+                .ok_or("missing field")?
+                .parse()
+                .map_err(|_| "invalid field")
+        };
+
+        let event = match kind {
+            "packet" => Event::Packet {
+                tick: next()? as u64,
+                src: next()? as usize,
+                dest: next()?,
+                packet: Packet {
+                    x: next()?,
+                    y: next()?,
+                },
+            },
+            "recv" => Event::Recv {
+                tick: next()? as u64,
+                addr: next()? as usize,
+                poll: next()? as u64,
+                packet: Packet {
+                    x: next()?,
+                    y: next()?,
+                },
+            },
+            "idle" => Event::Idle {
+                tick: next()? as u64,
+            },
+            "inject" => Event::Inject {
+                tick: next()? as u64,
+                dest: next()? as usize,
+                packet: Packet {
+                    x: next()?,
+                    y: next()?,
+                },
+            },
+            _ => return Err("unknown event"),
+        };
+
+        if fields.next().is_some() {
+            return Err("too many fields");
+        }
+        Ok(event)
+    }
+}
+
+pub fn format_capture(events: &[Event]) -> String {
+    let mut res = String::from("# intcode network capture\n");
+    for event in events {
+        res.push_str(&event.to_string());
+        res.push('\n');
+    }
+    res
+}
+
+pub fn parse_capture(input: &str) -> Result<Vec<Event>, &'static str> {
+    input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::parse)
+        .collect()
+}
+
+// the number of empty reads to allow after the captured inbound stream ends, before giving up on
+// the node sending the rest of its captured packets
+const MAX_TRAILING_POLLS: u64 = 10_000;
+
+// Runs node `addr` alone, feeding it the packets it read in the capture on the same input reads,
+// and the empty input on every other read. Returns the (dest, packet) pairs it sent.
+pub fn replay(
+    program: &Program,
+    addr: usize,
+    events: &[Event],
+    empty_input: i64,
+) -> Result<Vec<(i64, Packet)>, &'static str> {
+    let mut inbound = events
+        .iter()
+        .filter_map(|event| match *event {
+            Event::Recv {
+                addr: a,
+                poll,
+                packet,
+                ..
+            } if a == addr => Some((poll, packet)),
+            _ => None,
+        })
+        .peekable();
+    let expected_sent = events
+        .iter()
+        .filter(|event| matches!(event, Event::Packet { src, .. } if *src == addr))
+        .count();
+
+    let mut emu = Emulator::new(program.clone());
+    emu.add_input(addr as i64);
+    let mut sent = Vec::new();
+    let mut poll = 0;
+    let mut trailing_polls = 0;
+
+    loop {
+        match emu.run()? {
+            Status::Halted => return Err("emulator halted"),
+            Status::Output(dest) => {
+                let x = match emu.run()? {
+                    Status::Output(i) => i,
+                    _ => return Err("expected output"),
+                };
+                let y = match emu.run()? {
+                    Status::Output(i) => i,
+                    _ => return Err("expected output"),
+                };
+                sent.push((dest, Packet { x, y }));
+            }
+            Status::NeedsInput => {
+                poll += 1;
+                match inbound.peek() {
+                    Some(&(p, Packet { x, y })) if p == poll => {
+                        inbound.next();
+                        emu.add_input(x);
+                        emu.add_input(y);
+                    }
+                    Some(&(p, _)) if p < poll => return Err("capture polls out of order"),
+                    Some(_) => emu.add_input(empty_input),
+                    None => {
+                        if sent.len() >= expected_sent {
+                            return Ok(sent);
+                        }
+                        trailing_polls += 1;
+                        if trailing_polls > MAX_TRAILING_POLLS {
+                            return Err("node did not send its captured packets");
+                        }
+                        emu.add_input(empty_input);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_capture, parse_capture, Event};
+    use crate::network::Packet;
+
+    #[test]
+    fn test_capture_round_trip() {
+        let packet = Packet { x: 3, y: -7 };
+        let events = vec![
+            Event::Packet {
+                tick: 1,
+                src: 4,
+                dest: 255,
+                packet,
+            },
+            Event::Recv {
+                tick: 2,
+                addr: 0,
+                poll: 5,
+                packet,
+            },
+            Event::Idle { tick: 3 },
+            Event::Inject {
+                tick: 3,
+                dest: 0,
+                packet,
+            },
+        ];
+        assert_eq!(parse_capture(&format_capture(&events)), Ok(events));
+        assert!(parse_capture("packet 1 2 3").is_err());
+        assert!(parse_capture("idle 1 2").is_err());
+    }
+}
@@ -1,75 +1,135 @@
+mod capture;
 mod emulator;
-use crate::emulator::{Emulator, Program, Status};
-use std::cmp::min;
-use std::collections::VecDeque;
+mod network;
+mod scheduler;
+use crate::capture::{format_capture, parse_capture, replay, Event};
+use crate::emulator::Program;
+use crate::network::{
+    Config, ConsecutiveEmptyPolls, FirstPacket, Nat, Network, Packet, WakeOnIdle,
+};
+use crate::scheduler::{Random, RoundRobin, Scheduler, TimeSlice};
+use std::env;
+use std::fs;
 
-fn run(program: Program) {
-    let mut queues: Vec<VecDeque<(i64, i64)>> = vec![VecDeque::new(); 50];
-    let mut nat_queue: VecDeque<(i64, i64)> = VecDeque::new();
-    let mut idle = vec![0; 50];
-    let mut emus = Vec::new();
-    for addr in 0..50 {
-        let mut emu = Emulator::new(program.clone());
-        emu.add_input(addr);
-        emus.push(emu);
+fn run<N: Nat, S: Scheduler>(
+    program: &Program,
+    nat: &mut N,
+    scheduler: S,
+    capture_path: Option<&str>,
+) -> Result<N::Output, &'static str> {
+    let config = Config::default();
+    let idle = ConsecutiveEmptyPolls::new(config.node_count, 100);
+    let mut network = Network::new(program, &config, idle, scheduler);
+    if capture_path.is_some() {
+        network = network.with_capture();
     }
+    let res = network.run(nat);
+    if let Some(path) = capture_path {
+        fs::write(path, format_capture(network.captured())).expect("failed to write capture");
+    }
+    res
+}
 
-    let mut first_nat_packet = true;
-    let mut last_sent = None;
+fn replay_node(program: &Program, capture_path: &str, addr: usize) {
+    let capture = fs::read_to_string(capture_path).expect("failed to read capture");
+    let events = parse_capture(&capture).expect("failed to parse capture");
+    let sent = replay(program, addr, &events, Config::default().empty_input)
+        .expect("failed to replay node");
 
-    loop {
-        for (addr, emu) in emus.iter_mut().enumerate() {
-            match emu.run().unwrap() {
-                Status::Halted => panic!("emulator halted"),
-                Status::Output(dest) => {
-                    let x = match emu.run().unwrap() {
-                        Status::Output(i) => i,
-                        _ => panic!("expected output"),
-                    };
-                    let y = match emu.run().unwrap() {
-                        Status::Output(i) => i,
-                        _ => panic!("expected output"),
-                    };
-                    let queue = if dest == 255 {
-                        if first_nat_packet {
-                            println!("Part 1: Y = {}", y);
-                            first_nat_packet = false;
-                        }
-                        &mut nat_queue
-                    } else {
-                        &mut queues[dest as usize]
-                    };
-                    queue.push_back((x, y));
-                    idle[addr] = 0;
-                }
-                Status::NeedsInput => {
-                    if let Some((x, y)) = queues[addr].pop_front() {
-                        emu.add_input(x);
-                        emu.add_input(y);
-                        idle[addr] = 0;
-                    } else {
-                        emu.add_input(-1);
-                        idle[addr] = min(idle[addr] + 1, 100);
-                    }
-                }
-            }
-        }
-        if idle.iter().all(|&i| i == 100) {
-            let packet = *nat_queue.back().unwrap();
-            if Some(packet) == last_sent {
-                println!("Part 2: Y = {}", packet.1);
-                return;
-            } else {
-                last_sent = Some(packet);
-            }
-            queues[0].push_back(packet);
-            idle = vec![0; 50];
+    let expected: Vec<_> = events
+        .iter()
+        .filter_map(|event| match *event {
+            Event::Packet {
+                src, dest, packet, ..
+            } if src == addr => Some((dest, packet)),
+            _ => None,
+        })
+        .collect();
+    for (dest, packet) in &sent {
+        println!("{} -> {}: X = {}, Y = {}", addr, dest, packet.x, packet.y);
+    }
+    if sent == expected {
+        println!("Replay of node {} matches the capture", addr);
+    } else {
+        println!("Replay of node {} diverges from the capture", addr);
+    }
+}
+
+// returns the (part 1, part 2) packets under the given scheduler
+fn run_both<S: Scheduler>(
+    program: &Program,
+    make_scheduler: impl Fn() -> S,
+) -> Result<(Packet, Packet), &'static str> {
+    let part1 = run(program, &mut FirstPacket, make_scheduler(), None)?;
+    let part2 = run(program, &mut WakeOnIdle::new(0), make_scheduler(), None)?;
+    Ok((part1, part2))
+}
+
+// checks that the answers do not depend on the order the nodes run in
+fn check_schedulers(program: &Program) {
+    let mut results = vec![(
+        "round robin".to_string(),
+        run_both(program, RoundRobin::default),
+    )];
+    for &quantum in &[1, 10, 100, 1000] {
+        results.push((
+            format!("time slice, quantum {}", quantum),
+            run_both(program, || TimeSlice::new(quantum)),
+        ));
+    }
+    for seed in 1..=5 {
+        results.push((
+            format!("random, seed {}", seed),
+            run_both(program, || Random::new(seed, 100)),
+        ));
+    }
+
+    for (name, res) in &results {
+        match res {
+            Ok((part1, part2)) => println!("{}: Y = {}, Y = {}", name, part1.y, part2.y),
+            Err(e) => println!("{}: failed: {}", name, e),
         }
     }
+    let first = &results[0].1;
+    if results.iter().all(|(_, res)| res == first) {
+        println!("All schedulers agree");
+    } else {
+        println!("Schedulers disagree");
+    }
 }
 
 fn main() {
     let input = include_str!("input.txt");
     let program = Program::new(input).expect("failed to parse program");
-    run(program);
+
+    let usage = "usage: day23 [--capture <file>] | --replay <file> <addr> | --check-schedulers";
+    let args: Vec<String> = env::args().skip(1).collect();
+    let capture_path = match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        ["--replay", path, addr] => {
+            let addr = addr.parse().expect("invalid address");
+            replay_node(&program, path, addr);
+            return;
+        }
+        ["--check-schedulers"] => {
+            check_schedulers(&program);
+            return;
+        }
+        ["--capture", path] => Some(path),
+        [] => None,
+        _ => panic!("{}", usage),
+    };
+
+    let packet =
+        run(&program, &mut FirstPacket, RoundRobin::default(), None).expect("network failed");
+    println!("Part 1: Y = {}", packet.y);
+
+    // part 1 is a prefix of the part 2 run, so only the second run's traffic is captured
+    let packet = run(
+        &program,
+        &mut WakeOnIdle::new(0),
+        RoundRobin::default(),
+        capture_path,
+    )
+    .expect("network failed");
+    println!("Part 2: Y = {}", packet.y);
 }
@@ -0,0 +1,405 @@
+// simulate a network of intcode computers
+
+use crate::capture::Event;
+use crate::emulator::{Emulator, Program, Status};
+use crate::scheduler::{Scheduler, Slice};
+use std::collections::VecDeque;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Packet {
+    pub x: i64,
+    pub y: i64,
+}
+
+pub struct Config {
+    pub node_count: usize,
+    pub nat_address: i64,
+    // the value a node reads when its queue is empty
+    pub empty_input: i64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            node_count: 50,
+            nat_address: 255,
+            empty_input: -1,
+        }
+    }
+}
+
+// idle detection
+
+pub trait IdlePolicy {
+    // called when a node sends or receives a packet
+    fn activity(&mut self, addr: usize);
+    // called when a node reads from its empty queue
+    fn empty_poll(&mut self, addr: usize);
+    fn is_idle(&self) -> bool;
+    fn reset(&mut self);
+}
+
+// the network is idle once every node has polled an empty queue `threshold` times in a row
+pub struct ConsecutiveEmptyPolls {
+    threshold: usize,
+    polls: Vec<usize>,
+}
+
+impl ConsecutiveEmptyPolls {
+    pub fn new(node_count: usize, threshold: usize) -> Self {
+        ConsecutiveEmptyPolls {
+            threshold,
+            polls: vec![0; node_count],
+        }
+    }
+}
+
+impl IdlePolicy for ConsecutiveEmptyPolls {
+    fn activity(&mut self, addr: usize) {
+        self.polls[addr] = 0;
+    }
+
+    fn empty_poll(&mut self, addr: usize) {
+        self.polls[addr] = (self.polls[addr] + 1).min(self.threshold);
+    }
+
+    fn is_idle(&self) -> bool {
+        self.polls.iter().all(|&p| p == self.threshold)
+    }
+
+    fn reset(&mut self) {
+        for p in self.polls.iter_mut() {
+            *p = 0;
+        }
+    }
+}
+
+// NAT behavior
+
+pub enum NatAction<T> {
+    Continue,
+    Inject(usize, Packet),
+    Finish(T),
+}
+
+pub trait Nat {
+    type Output;
+    // called when a packet is sent to the NAT address
+    fn receive(&mut self, packet: Packet) -> NatAction<Self::Output>;
+    // called when the idle policy reports the network is idle
+    fn idle(&mut self) -> NatAction<Self::Output>;
+}
+
+// part 1: stop at the first packet sent to the NAT
+pub struct FirstPacket;
+
+impl Nat for FirstPacket {
+    type Output = Packet;
+
+    fn receive(&mut self, packet: Packet) -> NatAction<Packet> {
+        NatAction::Finish(packet)
+    }
+
+    fn idle(&mut self) -> NatAction<Packet> {
+        NatAction::Continue
+    }
+}
+
+// part 2: wake up `wake_address` with the last packet received while the network is idle, and
+// stop once the same packet would be delivered twice in a row
+pub struct WakeOnIdle {
+    wake_address: usize,
+    last_received: Option<Packet>,
+    last_sent: Option<Packet>,
+}
+
+impl WakeOnIdle {
+    pub fn new(wake_address: usize) -> Self {
+        WakeOnIdle {
+            wake_address,
+            last_received: None,
+            last_sent: None,
+        }
+    }
+}
+
+impl Nat for WakeOnIdle {
+    type Output = Packet;
+
+    fn receive(&mut self, packet: Packet) -> NatAction<Packet> {
+        self.last_received = Some(packet);
+        NatAction::Continue
+    }
+
+    fn idle(&mut self) -> NatAction<Packet> {
+        let packet = match self.last_received {
+            Some(packet) => packet,
+            None => return NatAction::Continue,
+        };
+        if self.last_sent == Some(packet) {
+            return NatAction::Finish(packet);
+        }
+        self.last_sent = Some(packet);
+        NatAction::Inject(self.wake_address, packet)
+    }
+}
+
+// network
+
+pub struct Network<I: IdlePolicy, S: Scheduler> {
+    nodes: Vec<Emulator>,
+    // output of each node that does not form a whole packet yet
+    pending: Vec<Vec<i64>>,
+    queues: Vec<VecDeque<Packet>>,
+    nat_address: i64,
+    empty_input: i64,
+    idle: I,
+    scheduler: S,
+
+    tick: u64,
+    polls: Vec<u64>,
+    capture: Option<Vec<Event>>,
+}
+
+impl<I: IdlePolicy, S: Scheduler> Network<I, S> {
+    pub fn new(program: &Program, config: &Config, idle: I, scheduler: S) -> Self {
+        let nodes = (0..config.node_count)
+            .map(|addr| {
+                let mut emu = Emulator::new(program.clone());
+                emu.add_input(addr as i64);
+                emu
+            })
+            .collect();
+
+        Network {
+            nodes,
+            pending: vec![Vec::with_capacity(3); config.node_count],
+            queues: vec![VecDeque::new(); config.node_count],
+            nat_address: config.nat_address,
+            empty_input: config.empty_input,
+            idle,
+            scheduler,
+            tick: 0,
+            polls: vec![0; config.node_count],
+            capture: None,
+        }
+    }
+
+    pub fn with_capture(mut self) -> Self {
+        self.capture = Some(Vec::new());
+        self
+    }
+
+    pub fn captured(&self) -> &[Event] {
+        self.capture.as_deref().unwrap_or(&[])
+    }
+
+    fn record(&mut self, event: Event) {
+        if let Some(capture) = &mut self.capture {
+            capture.push(event);
+        }
+    }
+
+    fn deliver(&mut self, dest: usize, packet: Packet) -> Result<(), &'static str> {
+        self.queues
+            .get_mut(dest)
+            .ok_or("packet sent to unknown address")?
+            .push_back(packet);
+        Ok(())
+    }
+
+    fn handle<T>(&mut self, action: NatAction<T>) -> Result<Option<T>, &'static str> {
+        match action {
+            NatAction::Continue => Ok(None),
+            NatAction::Inject(dest, packet) => {
+                self.record(Event::Inject {
+                    tick: self.tick,
+                    dest,
+                    packet,
+                });
+                self.deliver(dest, packet)?;
+                Ok(None)
+            }
+            NatAction::Finish(res) => Ok(Some(res)),
+        }
+    }
+
+    fn handle_output<N: Nat>(
+        &mut self,
+        addr: usize,
+        val: i64,
+        nat: &mut N,
+    ) -> Result<Option<N::Output>, &'static str> {
+        self.pending[addr].push(val);
+        if self.pending[addr].len() < 3 {
+            return Ok(None);
+        }
+        let dest = self.pending[addr][0];
+        let packet = Packet {
+            x: self.pending[addr][1],
+            y: self.pending[addr][2],
+        };
+        self.pending[addr].clear();
+        self.idle.activity(addr);
+
+        self.record(Event::Packet {
+            tick: self.tick,
+            src: addr,
+            dest,
+            packet,
+        });
+        if dest == self.nat_address {
+            let action = nat.receive(packet);
+            self.handle(action)
+        } else {
+            self.deliver(dest as usize, packet)?;
+            Ok(None)
+        }
+    }
+
+    fn handle_input(&mut self, addr: usize) {
+        self.polls[addr] += 1;
+        if let Some(packet) = self.queues[addr].pop_front() {
+            self.nodes[addr].add_input(packet.x);
+            self.nodes[addr].add_input(packet.y);
+            self.idle.activity(addr);
+            self.record(Event::Recv {
+                tick: self.tick,
+                addr,
+                poll: self.polls[addr],
+                packet,
+            });
+        } else {
+            self.nodes[addr].add_input(self.empty_input);
+            self.idle.empty_poll(addr);
+        }
+    }
+
+    fn run_slice<N: Nat>(
+        &mut self,
+        slice: Slice,
+        nat: &mut N,
+    ) -> Result<Option<N::Output>, &'static str> {
+        self.tick += 1;
+        let addr = slice.addr;
+        let deadline = slice
+            .instructions
+            .map(|instructions| self.nodes[addr].instructions() + instructions);
+
+        loop {
+            let status = match deadline {
+                None => self.nodes[addr].run()?,
+                Some(deadline) => {
+                    let remaining = deadline.saturating_sub(self.nodes[addr].instructions());
+                    match self.nodes[addr].run_for(remaining)? {
+                        Some(status) => status,
+                        None => return Ok(None),
+                    }
+                }
+            };
+
+            match status {
+                Status::Halted => return Err("emulator halted"),
+                Status::Output(val) => {
+                    if let Some(res) = self.handle_output(addr, val, nat)? {
+                        return Ok(Some(res));
+                    }
+                    // without a budget, the slice ends once a whole packet is sent
+                    if deadline.is_none() && self.pending[addr].is_empty() {
+                        return Ok(None);
+                    }
+                }
+                Status::NeedsInput => {
+                    self.handle_input(addr);
+                    if deadline.is_none() {
+                        return Ok(None);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn run<N: Nat>(&mut self, nat: &mut N) -> Result<N::Output, &'static str> {
+        let node_count = self.nodes.len();
+        loop {
+            // check for idleness after as many slices as there are nodes
+            for _ in 0..node_count {
+                let slice = self.scheduler.next(node_count);
+                if let Some(res) = self.run_slice(slice, nat)? {
+                    return Ok(res);
+                }
+            }
+
+            if self.idle.is_idle() {
+                self.record(Event::Idle { tick: self.tick });
+                let action = nat.idle();
+                if let Some(res) = self.handle(action)? {
+                    return Ok(res);
+                }
+                self.idle.reset();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ConsecutiveEmptyPolls, FirstPacket, IdlePolicy, Nat, NatAction, Packet, WakeOnIdle,
+    };
+
+    const A: Packet = Packet { x: 1, y: 2 };
+    const B: Packet = Packet { x: 3, y: 4 };
+
+    #[test]
+    fn test_first_packet() {
+        let mut nat = FirstPacket;
+        assert!(matches!(nat.idle(), NatAction::Continue));
+        assert!(matches!(nat.receive(A), NatAction::Finish(A)));
+    }
+
+    #[test]
+    fn test_wake_on_idle() {
+        let mut nat = WakeOnIdle::new(0);
+        // nothing to wake the network with yet
+        assert!(matches!(nat.idle(), NatAction::Continue));
+
+        assert!(matches!(nat.receive(A), NatAction::Continue));
+        assert!(matches!(nat.idle(), NatAction::Inject(0, A)));
+        // only the last packet received is kept
+        assert!(matches!(nat.receive(A), NatAction::Continue));
+        assert!(matches!(nat.receive(B), NatAction::Continue));
+        assert!(matches!(nat.idle(), NatAction::Inject(0, B)));
+        assert!(matches!(nat.receive(B), NatAction::Continue));
+        assert!(matches!(nat.idle(), NatAction::Finish(B)));
+    }
+
+    #[test]
+    fn test_consecutive_empty_polls() {
+        let mut idle = ConsecutiveEmptyPolls::new(2, 2);
+        assert!(!idle.is_idle());
+        for _ in 0..2 {
+            idle.empty_poll(0);
+            idle.empty_poll(1);
+        }
+        assert!(idle.is_idle());
+
+        // activity on one node starts its count again
+        idle.activity(1);
+        assert!(!idle.is_idle());
+        idle.empty_poll(1);
+        assert!(!idle.is_idle());
+        idle.empty_poll(1);
+        assert!(idle.is_idle());
+
+        // extra polls don't count past the threshold
+        idle.empty_poll(0);
+        idle.activity(1);
+        idle.empty_poll(1);
+        idle.empty_poll(1);
+        assert!(idle.is_idle());
+
+        idle.reset();
+        assert!(!idle.is_idle());
+    }
+}
@@ -0,0 +1,142 @@
+// choose which node runs next, and for how long
+
+pub struct Slice {
+    pub addr: usize,
+    // None runs the node until it sends a packet or reads its input
+    pub instructions: Option<u64>,
+}
+
+pub trait Scheduler {
+    fn next(&mut self, node_count: usize) -> Slice;
+}
+
+// nodes in address order, each until it sends a packet or reads its input
+#[derive(Default)]
+pub struct RoundRobin {
+    addr: usize,
+}
+
+impl Scheduler for RoundRobin {
+    fn next(&mut self, node_count: usize) -> Slice {
+        let addr = self.addr;
+        self.addr = (self.addr + 1) % node_count;
+        Slice {
+            addr,
+            instructions: None,
+        }
+    }
+}
+
+// nodes in address order, each for a fixed number of instructions
+pub struct TimeSlice {
+    addr: usize,
+    quantum: u64,
+}
+
+impl TimeSlice {
+    pub fn new(quantum: u64) -> Self {
+        TimeSlice {
+            addr: 0,
+            // a node given no instructions would never get anywhere
+            quantum: quantum.max(1),
+        }
+    }
+}
+
+impl Scheduler for TimeSlice {
+    fn next(&mut self, node_count: usize) -> Slice {
+        let addr = self.addr;
+        self.addr = (self.addr + 1) % node_count;
+        Slice {
+            addr,
+            instructions: Some(self.quantum),
+        }
+    }
+}
+
+// random nodes for a random number of instructions, up to `max_quantum`
+pub struct Random {
+    state: u64,
+    max_quantum: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64, max_quantum: u64) -> Self {
+        Random {
+            // xorshift gets stuck on a zero state
+            state: seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1,
+            max_quantum: max_quantum.max(1),
+        }
+    }
+
+    // xorshift64*
+    fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+}
+
+impl Scheduler for Random {
+    fn next(&mut self, node_count: usize) -> Slice {
+        let addr = (self.next_u64() % node_count as u64) as usize;
+        let instructions = 1 + self.next_u64() % self.max_quantum;
+        Slice {
+            addr,
+            instructions: Some(instructions),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Random, RoundRobin, Scheduler, TimeSlice};
+    use crate::emulator::Program;
+    use crate::network::{
+        Config, ConsecutiveEmptyPolls, FirstPacket, Nat, Network, Packet, WakeOnIdle,
+    };
+
+    // node 0 sends (10, 20) to node 1, which waits for it and sends (x + y, y) to the NAT; both
+    // then poll their input forever
+    fn network_program() -> Program {
+        let code = "3,100,1005,100,16,\
+                    104,1,104,10,104,20,\
+                    3,101,1105,1,11,\
+                    3,101,1008,101,-1,102,1005,102,16,\
+                    3,103,1,101,103,104,\
+                    104,255,4,104,4,103,\
+                    1105,1,11";
+        Program::new(code).unwrap()
+    }
+
+    fn run<S: Scheduler, N: Nat>(scheduler: S, nat: &mut N) -> N::Output {
+        let config = Config {
+            node_count: 2,
+            ..Config::default()
+        };
+        let idle = ConsecutiveEmptyPolls::new(2, 2);
+        Network::new(&network_program(), &config, idle, scheduler)
+            .run(nat)
+            .unwrap()
+    }
+
+    fn check<S: Scheduler>(scheduler: impl Fn() -> S) {
+        let expected = Packet { x: 30, y: 20 };
+        assert_eq!(run(scheduler(), &mut FirstPacket), expected);
+        // the packet woken node 0 with is ignored, so the NAT sends it again
+        assert_eq!(run(scheduler(), &mut WakeOnIdle::new(0)), expected);
+    }
+
+    #[test]
+    fn test_schedulers_agree() {
+        check(RoundRobin::default);
+        for quantum in 0..5 {
+            check(|| TimeSlice::new(quantum));
+        }
+        for seed in 0..5 {
+            check(|| Random::new(seed, 0));
+            check(|| Random::new(seed, 7));
+        }
+    }
+}
@@ -0,0 +1,198 @@
+// parse the text adventure's output
+
+use crate::ascii::{AsciiComputer, Event};
+use crate::emulator::Program;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Room {
+    pub name: String,
+    pub description: String,
+    pub doors: Vec<String>,
+    pub items: Vec<String>,
+}
+
+// everything printed in response to a command
+#[derive(Debug, Default)]
+pub struct Response {
+    // rooms are listed in the order they were entered
+    pub rooms: Vec<Room>,
+    // lines that are not part of a room description
+    pub messages: Vec<String>,
+    pub non_ascii: Vec<i64>,
+    pub halted: bool,
+}
+
+impl Response {
+    // the room the droid ended up in, if it moved
+    pub fn room(&self) -> Option<&Room> {
+        self.rooms.last()
+    }
+}
+
+enum Section {
+    None,
+    Description,
+    Doors,
+    Items,
+}
+
+pub fn parse_response(events: &[Event]) -> Response {
+    let mut res = Response::default();
+    let mut section = Section::None;
+
+    for event in events {
+        let line = match event {
+            Event::Line(line) => line.trim_end(),
+            Event::NonAscii(val) => {
+                res.non_ascii.push(*val);
+                continue;
+            }
+            Event::NeedsInput => continue,
+            Event::Halted => {
+                res.halted = true;
+                continue;
+            }
+        };
+
+        if line.starts_with("== ") && line.ends_with(" ==") && line.len() >= 6 {
+            res.rooms.push(Room {
+                name: line[3..line.len() - 3].to_string(),
+                description: String::new(),
+                doors: Vec::new(),
+                items: Vec::new(),
+            });
+            section = Section::Description;
+            continue;
+        }
+
+        match line {
+            "" => section = Section::None,
+            "Command?" => {}
+            "Doors here lead:" if !res.rooms.is_empty() => section = Section::Doors,
+            "Items here:" if !res.rooms.is_empty() => section = Section::Items,
+            _ => match (&section, line.strip_prefix("- "), res.rooms.last_mut()) {
+                (Section::Doors, Some(door), Some(room)) => room.doors.push(door.to_string()),
+                (Section::Items, Some(item), Some(room)) => room.items.push(item.to_string()),
+                (Section::Description, _, Some(room)) => {
+                    if !room.description.is_empty() {
+                        room.description.push('\n');
+                    }
+                    room.description.push_str(line);
+                }
+                _ => {
+                    section = Section::None;
+                    res.messages.push(line.to_string());
+                }
+            },
+        }
+    }
+
+    res
+}
+
+pub fn opposite(door: &str) -> Option<&'static str> {
+    Some(match door {
+        "north" => "south",
+        "south" => "north",
+        "east" => "west",
+        "west" => "east",
+        _ => return None,
+    })
+}
+
+// the droid in the text adventure
+#[derive(Clone)]
+pub struct Droid {
+    computer: AsciiComputer,
+}
+
+impl Droid {
+    // returns the droid, and the description of the starting room
+    pub fn new(program: Program) -> Result<(Droid, Response), &'static str> {
+        let mut droid = Droid {
+            computer: AsciiComputer::new(program),
+        };
+        let response = parse_response(&droid.computer.wait_for("Command?")?);
+        Ok((droid, response))
+    }
+
+    pub fn command(&mut self, command: &str) -> Result<Response, &'static str> {
+        self.computer.send_line(command);
+        Ok(parse_response(&self.computer.read_until_input()?))
+    }
+
+    // like command, but returns Ok(None) if the program runs for more than `max_instructions`
+    pub fn command_within(
+        &mut self,
+        command: &str,
+        max_instructions: u64,
+    ) -> Result<Option<Response>, &'static str> {
+        self.computer.send_line(command);
+        let events = self.computer.read_until_input_within(max_instructions)?;
+        Ok(events.map(|events| parse_response(&events)))
+    }
+
+    // moves through each door in turn, returning the response to the last move
+    pub fn walk(&mut self, doors: &[String]) -> Result<Response, &'static str> {
+        let mut response = Response::default();
+        for door in doors {
+            response = self.command(door)?;
+            if response.room().is_none() {
+                return Err("droid could not move");
+            }
+        }
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_response;
+    use crate::ascii::Event;
+
+    #[test]
+    fn test_parse_response() {
+        let output = "
+
+== Pressure-Sensitive Floor ==
+Analyzing...
+
+Doors here lead:
+- north
+
+A loud, robotic voice says \"Alert!\" and you are ejected back to the checkpoint.
+
+
+
+== Security Checkpoint ==
+In the next room, a pressure-sensitive floor will verify your identity.
+
+Doors here lead:
+- north
+- south
+
+Items here:
+- sand
+
+Command?";
+        let mut events: Vec<Event> = output.lines().map(|l| Event::Line(l.to_string())).collect();
+        events.push(Event::NeedsInput);
+        let res = parse_response(&events);
+
+        assert_eq!(res.rooms.len(), 2);
+        assert_eq!(res.rooms[0].name, "Pressure-Sensitive Floor");
+        assert_eq!(res.rooms[0].description, "Analyzing...");
+        assert_eq!(res.rooms[0].doors, vec!["north"]);
+        let room = res.room().unwrap();
+        assert_eq!(room.name, "Security Checkpoint");
+        assert_eq!(room.doors, vec!["north", "south"]);
+        assert_eq!(room.items, vec!["sand"]);
+        assert_eq!(
+            res.messages,
+            vec![
+                "A loud, robotic voice says \"Alert!\" and you are ejected back to the checkpoint."
+            ]
+        );
+        assert!(!res.halted);
+    }
+}
@@ -0,0 +1,150 @@
+// find the set of items that gets the droid past the pressure-sensitive floor
+
+use crate::adventure::{Droid, Response};
+use crate::map::RoomGraph;
+
+#[derive(Debug, Eq, PartialEq)]
+enum Feedback {
+    // the droids on the ship are heavier
+    TooLight,
+    // the droids on the ship are lighter
+    TooHeavy,
+    Accepted,
+}
+
+fn parse_feedback(response: &Response) -> Result<Feedback, &'static str> {
+    let text = response.messages.join(" ");
+    if text.contains("heavier than the detected value") {
+        Ok(Feedback::TooLight)
+    } else if text.contains("lighter than the detected value") {
+        Ok(Feedback::TooHeavy)
+    } else if response.halted {
+        Ok(Feedback::Accepted)
+    } else {
+        Err("unexpected response from the pressure-sensitive floor")
+    }
+}
+
+fn parse_password(response: &Response) -> Option<String> {
+    let text = response.messages.join(" ");
+    let start = text.find("typing ")? + "typing ".len();
+    let password: String = text[start..]
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect();
+    if password.is_empty() {
+        None
+    } else {
+        Some(password)
+    }
+}
+
+pub struct Solution {
+    pub password: String,
+    pub items: Vec<String>,
+    // number of times the droid stepped on the floor
+    pub weighings: usize,
+    // number of take and drop commands
+    pub swaps: usize,
+}
+
+// Walks the droid to the checkpoint, then tries subsets of `items` (which it must be carrying)
+// in Gray code order, so each subset differs from the last by one take or drop. Subsets known to
+// be too light or too heavy from earlier feedback are passed over without weighing them.
+pub fn solve(
+    droid: &mut Droid,
+    graph: &RoomGraph,
+    from: &str,
+    items: &[String],
+) -> Result<Solution, &'static str> {
+    if items.len() >= 32 {
+        return Err("too many items");
+    }
+    let (checkpoint, door) = graph
+        .checkpoint
+        .as_ref()
+        .ok_or("no checkpoint on the map")?;
+    droid.walk(
+        &graph
+            .path(from, checkpoint)
+            .ok_or("checkpoint is unreachable")?,
+    )?;
+
+    // bit i of a mask is set if the droid carries items[i]
+    let all = (1u32 << items.len()) - 1;
+    let mut carrying = all;
+    let mut too_light: Vec<u32> = Vec::new();
+    let mut too_heavy: Vec<u32> = Vec::new();
+    let mut weighings = 0;
+    let mut swaps = 0;
+
+    for i in 0..=all {
+        // the Gray code of i is the set of items dropped
+        let next = all & !(i ^ (i >> 1));
+        let changed = carrying ^ next;
+        if changed != 0 {
+            let item = &items[changed.trailing_zeros() as usize];
+            let command = if next & changed != 0 { "take" } else { "drop" };
+            droid.command(&format!("{} {}", command, item))?;
+            carrying = next;
+            swaps += 1;
+        }
+
+        if too_light.iter().any(|&light| carrying & !light == 0)
+            || too_heavy.iter().any(|&heavy| heavy & !carrying == 0)
+        {
+            continue;
+        }
+
+        let response = droid.command(door)?;
+        weighings += 1;
+        match parse_feedback(&response)? {
+            Feedback::TooLight => too_light.push(carrying),
+            Feedback::TooHeavy => too_heavy.push(carrying),
+            Feedback::Accepted => {
+                return Ok(Solution {
+                    password: parse_password(&response).ok_or("no password in response")?,
+                    items: (0..items.len())
+                        .filter(|&i| carrying & (1 << i) != 0)
+                        .map(|i| items[i].clone())
+                        .collect(),
+                    weighings,
+                    swaps,
+                })
+            }
+        }
+    }
+
+    Err("no subset of items is accepted")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_feedback, parse_password, Feedback};
+    use crate::adventure::Response;
+
+    #[test]
+    fn test_parse_feedback() {
+        let mut response = Response {
+            messages: vec![
+                "A loud, robotic voice says \"Alert! Droids on this ship are heavier than the \
+                 detected value!\" and you are ejected back to the checkpoint."
+                    .to_string(),
+            ],
+            ..Response::default()
+        };
+        assert_eq!(parse_feedback(&response), Ok(Feedback::TooLight));
+
+        response.messages = vec![
+            "Santa notices your small droid, looks puzzled for a moment, realizes what has \
+             happened, and radios your ship directly."
+                .to_string(),
+            "\"Oh, hello! You should be able to get in by typing 12345 on the keypad at the main \
+             airlock.\""
+                .to_string(),
+        ];
+        response.halted = true;
+        assert_eq!(parse_feedback(&response), Ok(Feedback::Accepted));
+        assert_eq!(parse_password(&response), Some("12345".to_string()));
+    }
+}
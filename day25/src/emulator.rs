@@ -91,11 +91,13 @@ pub enum Status {
     Output(i64),
 }
 
+#[derive(Clone)]
 pub struct Emulator {
     memory: Vec<i64>,
     ip: i64,
     relative_base: i64,
     input: VecDeque<i64>,
+    instructions: u64,
 }
 
 impl Emulator {
@@ -105,6 +107,7 @@ impl Emulator {
             ip: 0,
             relative_base: 0,
             input: VecDeque::new(),
+            instructions: 0,
         }
     }
 
@@ -112,6 +115,11 @@ impl Emulator {
         self.input.push_back(val);
     }
 
+    /// number of instructions executed so far
+    pub fn instructions(&self) -> u64 {
+        self.instructions
+    }
+
     fn get(&self, address: i64) -> i64 {
         let address = address as usize;
         if address >= self.memory.len() {
@@ -125,7 +133,7 @@ impl Emulator {
         let address = address as usize;
         if address >= self.memory.len() {
             self.memory
-                .extend(iter::repeat(0).take(address - self.memory.len() + 1))
+                .extend(iter::repeat_n(0, address - self.memory.len() + 1))
         }
         self.memory[address] = value;
     }
@@ -180,6 +188,7 @@ impl Emulator {
             Opcode::Output => {
                 let arg = self.get_arg_val(1, instr.p1_mode);
                 self.ip += 2;
+                self.instructions += 1;
                 return Ok(Some(Status::Output(arg)));
             }
             Opcode::JumpIfTrue => {
@@ -224,6 +233,7 @@ impl Emulator {
             }
         }
 
+        self.instructions += 1;
         Ok(None)
     }
 
@@ -234,4 +244,15 @@ impl Emulator {
             }
         }
     }
+
+    /// like run, but returns Ok(None) if `max_instructions` run without a special status
+    pub fn run_for(&mut self, max_instructions: u64) -> Result<Option<Status>, &'static str> {
+        let deadline = self.instructions + max_instructions;
+        while self.instructions < deadline {
+            if let Some(status) = self.step()? {
+                return Ok(Some(status));
+            }
+        }
+        Ok(None)
+    }
 }
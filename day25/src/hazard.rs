@@ -0,0 +1,81 @@
+// find out which items are safe to take, by taking them in a copy of the droid
+
+use crate::adventure::{Droid, Room};
+use crate::map::RoomGraph;
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum Outcome {
+    Safe,
+    // the program halted, with the message it printed
+    Fatal(String),
+    // the program ran past the instruction budget
+    Stuck,
+    // the droid can no longer move, with the message it printed
+    Trapped(String),
+}
+
+pub struct Trial {
+    pub item: String,
+    pub room: String,
+    pub outcome: Outcome,
+}
+
+fn try_command(droid: &mut Droid, command: &str, budget: u64) -> Result<Outcome, &'static str> {
+    Ok(match droid.command_within(command, budget)? {
+        None => Outcome::Stuck,
+        Some(response) if response.halted => Outcome::Fatal(response.messages.join(" ")),
+        Some(_) => Outcome::Safe,
+    })
+}
+
+// takes the item with a droid in the item's room, then checks the droid can still leave
+fn try_item(droid: &Droid, room: &Room, item: &str, budget: u64) -> Result<Outcome, &'static str> {
+    let mut droid = droid.clone();
+    let outcome = try_command(&mut droid, &format!("take {}", item), budget)?;
+    if outcome != Outcome::Safe {
+        return Ok(outcome);
+    }
+
+    let door = room.doors.first().ok_or("room has no doors")?;
+    Ok(match droid.command_within(door, budget)? {
+        None => Outcome::Stuck,
+        Some(response) if response.halted => Outcome::Fatal(response.messages.join(" ")),
+        Some(response) if response.room().is_none() => {
+            Outcome::Trapped(response.messages.join(" "))
+        }
+        Some(_) => Outcome::Safe,
+    })
+}
+
+// tries every item on the map, with a droid in the graph's starting room
+pub fn try_items(
+    droid: &Droid,
+    graph: &RoomGraph,
+    budget: u64,
+) -> Result<Vec<Trial>, &'static str> {
+    let mut res = Vec::new();
+    for room in graph.rooms.values().filter(|room| !room.items.is_empty()) {
+        let path = graph
+            .path(&graph.start, &room.name)
+            .ok_or("room is unreachable")?;
+        let mut visitor = droid.clone();
+        visitor.walk(&path)?;
+
+        for item in &room.items {
+            res.push(Trial {
+                item: item.clone(),
+                room: room.name.clone(),
+                outcome: try_item(&visitor, room, item, budget)?,
+            });
+        }
+    }
+    Ok(res)
+}
+
+pub fn safe_items(trials: &[Trial]) -> Vec<String> {
+    trials
+        .iter()
+        .filter(|trial| trial.outcome == Outcome::Safe)
+        .map(|trial| trial.item.clone())
+        .collect()
+}
@@ -1,45 +1,130 @@
+mod adventure;
+mod ascii;
+mod checkpoint;
 mod emulator;
-use emulator::{Emulator, Program, Status};
-use std::io::{self, Read, Write};
-
-fn run_ascii_program(program: Program) {
-    let mut emu = Emulator::new(program);
-    loop {
-        let status = emu.run().expect("emulator error");
-        match status {
-            Status::Halted => return,
-            Status::Output(out) => {
-                if out < 255 {
-                    io::stdout().write_all(&[out as u8]).expect("write failed");
-                } else {
-                    println!("Non-ASCII Output: {}", out);
-                }
-            }
-            Status::NeedsInput => {
-                let mut buf = [0];
-                io::stdin().read_exact(&mut buf).expect("read failed");
-                emu.add_input(buf[0] as i64);
-            }
-        }
+mod hazard;
+mod map;
+mod terminal;
+use adventure::Droid;
+use emulator::Program;
+use hazard::Outcome;
+use std::env;
+use std::fs::{self, File};
+use std::io;
+use terminal::Terminal;
+
+fn map_rooms(program: Program, dot_path: Option<String>) {
+    let (mut droid, response) = Droid::new(program).expect("failed to start droid");
+    let start = response.room().expect("no starting room");
+    let graph = map::explore(&mut droid, start).expect("failed to explore");
+    print!("{}", graph);
+    if let Some(path) = dot_path {
+        fs::write(path, graph.to_dot()).expect("failed to write DOT file");
     }
 }
 
+// enough for any normal command, by a wide margin
+const COMMAND_BUDGET: u64 = 1_000_000;
+
+fn find_hazards(program: Program) {
+    let (mut droid, response) = Droid::new(program).expect("failed to start droid");
+    let start = response.room().expect("no starting room");
+    let graph = map::explore(&mut droid, start).expect("failed to explore");
+    let trials = hazard::try_items(&droid, &graph, COMMAND_BUDGET).expect("failed to try items");
+
+    for trial in &trials {
+        let outcome = match &trial.outcome {
+            Outcome::Safe => "safe".to_string(),
+            Outcome::Fatal(message) => format!("fatal: {}", message),
+            Outcome::Stuck => "stuck".to_string(),
+            Outcome::Trapped(message) => format!("trapped: {}", message),
+        };
+        println!("{} ({}): {}", trial.item, trial.room, outcome);
+    }
+    println!("Safe items: {}", hazard::safe_items(&trials).join(", "));
+}
+
+fn print_solution(solution: &checkpoint::Solution) {
+    println!("Items: {}", solution.items.join(", "));
+    println!(
+        "Weighings: {}, take/drop commands: {}",
+        solution.weighings, solution.swaps
+    );
+    println!("Password: {}", solution.password);
+}
+
+fn solve_checkpoint(program: Program, items: &[String]) {
+    let (mut droid, response) = Droid::new(program).expect("failed to start droid");
+    let start = response.room().expect("no starting room");
+    let graph = map::explore(&mut droid, start).expect("failed to explore");
+    let room =
+        map::collect(&mut droid, &graph, &start.name, items).expect("failed to collect items");
+    let solution =
+        checkpoint::solve(&mut droid, &graph, &room, items).expect("failed to pass checkpoint");
+    print_solution(&solution);
+}
+
+// map the ship, pick up every safe item, and find the subset the checkpoint accepts
+fn solve(program: Program) -> Result<checkpoint::Solution, &'static str> {
+    let (mut droid, response) = Droid::new(program)?;
+    let start = response.room().ok_or("no starting room")?;
+    let graph = map::explore(&mut droid, start)?;
+    let trials = hazard::try_items(&droid, &graph, COMMAND_BUDGET)?;
+    let items = hazard::safe_items(&trials);
+    let room = map::collect(&mut droid, &graph, &start.name, &items)?;
+    checkpoint::solve(&mut droid, &graph, &room, &items)
+}
+
+enum Mode {
+    Solve,
+    Interactive,
+    Map(Option<String>),
+    Hazards,
+    Checkpoint(Vec<String>),
+}
+
 fn main() {
-    let input = include_str!("input.txt");
-    let program = Program::new(input).unwrap();
-    run_ascii_program(program);
-    // dont take:
-    // - photons
-    // - giant electromagnet
-    // - infinite loop
-    // - escape pod
-    // - molten lava
-
-    // to get password need:
-    // - ornament
-    // - astrolabe
-    // - sand
-    // - shell
-
-    // password: 134807554
+    let usage = "usage: day25 [--program <file>] [--interactive [--script <file>] \
+                 [--record <file>] | --map [--dot <file>] | --hazards | \
+                 --checkpoint <item,item,...>]";
+    let mut code = include_str!("input.txt").to_string();
+    let mut script = String::new();
+    let mut record = None;
+    let mut mode = Mode::Solve;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut path = || args.next().expect(usage);
+        match arg.as_str() {
+            "--program" => code = fs::read_to_string(path()).expect("failed to read program"),
+            "--interactive" => mode = Mode::Interactive,
+            "--script" => script = fs::read_to_string(path()).expect("failed to read script"),
+            "--record" => record = Some(File::create(path()).expect("failed to create transcript")),
+            "--map" => mode = Mode::Map(None),
+            "--dot" => mode = Mode::Map(Some(path())),
+            "--hazards" => mode = Mode::Hazards,
+            "--checkpoint" => {
+                mode = Mode::Checkpoint(path().split(',').map(|s| s.trim().to_string()).collect())
+            }
+            _ => panic!("{}", usage),
+        }
+    }
+
+    let program = Program::new(code.trim()).expect("failed to parse program");
+    match mode {
+        Mode::Solve => print_solution(&solve(program).expect("failed to solve")),
+        Mode::Interactive => {
+            let mut terminal = Terminal::new(program).with_script(&script);
+            if let Some(record) = record {
+                terminal = terminal.with_transcript(record);
+            }
+            let stdin = io::stdin();
+            terminal
+                .run(&mut stdin.lock())
+                .expect("failed to run program");
+        }
+        Mode::Map(dot_path) => map_rooms(program, dot_path),
+        Mode::Hazards => find_hazards(program),
+        Mode::Checkpoint(items) => solve_checkpoint(program, &items),
+    }
 }
@@ -0,0 +1,198 @@
+// map the text adventure by walking every door
+
+use crate::adventure::{opposite, Droid, Room};
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::fmt::{Display, Error, Formatter};
+
+pub struct RoomGraph {
+    pub start: String,
+    pub rooms: BTreeMap<String, Room>,
+    // room name -> door -> name of the room behind it
+    pub doors: BTreeMap<String, BTreeMap<String, String>>,
+    // the room and door where the droid was sent back, i.e. the security checkpoint
+    pub checkpoint: Option<(String, String)>,
+}
+
+impl RoomGraph {
+    fn new(start: &Room) -> Self {
+        let mut graph = RoomGraph {
+            start: start.name.clone(),
+            rooms: BTreeMap::new(),
+            doors: BTreeMap::new(),
+            checkpoint: None,
+        };
+        graph.add_room(start);
+        graph
+    }
+
+    // returns true if the room was not known yet
+    fn add_room(&mut self, room: &Room) -> bool {
+        self.doors.entry(room.name.clone()).or_default();
+        self.rooms.insert(room.name.clone(), room.clone()).is_none()
+    }
+
+    fn link(&mut self, from: &str, door: &str, to: &str) {
+        self.doors
+            .entry(from.to_string())
+            .or_default()
+            .insert(door.to_string(), to.to_string());
+    }
+
+    fn explored(&self, room: &str, door: &str) -> bool {
+        self.doors
+            .get(room)
+            .is_some_and(|doors| doors.contains_key(door))
+    }
+
+    // returns the doors to take from one room to another, by breadth first search
+    pub fn path(&self, from: &str, to: &str) -> Option<Vec<String>> {
+        let mut seen = HashSet::new();
+        let mut queue = VecDeque::new();
+        queue.push_back((from, Vec::new()));
+
+        while let Some((room, path)) = queue.pop_front() {
+            if room == to {
+                return Some(path);
+            }
+            if !seen.insert(room) {
+                continue;
+            }
+            for (door, next) in self.doors.get(room)? {
+                let mut path = path.clone();
+                path.push(door.clone());
+                queue.push_back((next.as_str(), path));
+            }
+        }
+
+        None
+    }
+
+    pub fn to_dot(&self) -> String {
+        let quote = |s: &str| format!("\"{}\"", s.replace('"', "\\\""));
+        let mut res = String::from("digraph adventure {\n");
+        for room in self.rooms.values() {
+            let mut label = room.name.clone();
+            for item in &room.items {
+                label.push_str("\\n");
+                label.push_str(item);
+            }
+            let shape = if room.name == self.start {
+                "doublecircle"
+            } else {
+                "box"
+            };
+            res.push_str(&format!(
+                "    {} [label={}, shape={}];\n",
+                quote(&room.name),
+                quote(&label),
+                shape
+            ));
+        }
+        for (from, doors) in &self.doors {
+            for (door, to) in doors {
+                res.push_str(&format!(
+                    "    {} -> {} [label={}];\n",
+                    quote(from),
+                    quote(to),
+                    quote(door)
+                ));
+            }
+        }
+        res.push_str("}\n");
+        res
+    }
+}
+
+impl Display for RoomGraph {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        for room in self.rooms.values() {
+            writeln!(f, "== {} ==", room.name)?;
+            for door in &room.doors {
+                match self.doors[&room.name].get(door) {
+                    Some(to) => writeln!(f, "  {} -> {}", door, to)?,
+                    None => writeln!(f, "  {} -> ?", door)?,
+                }
+            }
+            if !room.items.is_empty() {
+                writeln!(f, "  items: {}", room.items.join(", "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn visit(droid: &mut Droid, graph: &mut RoomGraph, room: &Room) -> Result<(), &'static str> {
+    for door in &room.doors {
+        if graph.explored(&room.name, door) {
+            continue;
+        }
+
+        let response = droid.command(door)?;
+        if response.halted {
+            return Err("droid halted while exploring");
+        }
+        let entered = response
+            .rooms
+            .first()
+            .ok_or("door did not lead to a room")?;
+        let ended = response.room().unwrap();
+
+        let is_new = graph.add_room(entered);
+        graph.link(&room.name, door, &entered.name);
+        let back = opposite(door).ok_or("unknown door")?;
+        if entered.doors.iter().any(|d| d == back) {
+            graph.link(&entered.name, back, &room.name);
+        }
+
+        if ended.name != entered.name {
+            // the droid did not stay, e.g. it was ejected from the pressure-sensitive floor
+            if ended.name != room.name {
+                return Err("droid was moved to an unexpected room");
+            }
+            graph.checkpoint = Some((room.name.clone(), door.clone()));
+            continue;
+        }
+
+        if is_new {
+            visit(droid, graph, entered)?;
+        }
+        droid.command(back)?;
+    }
+
+    Ok(())
+}
+
+// depth first search through every door, returning the droid to where it started
+pub fn explore(droid: &mut Droid, start: &Room) -> Result<RoomGraph, &'static str> {
+    let mut graph = RoomGraph::new(start);
+    visit(droid, &mut graph, start)?;
+    Ok(graph)
+}
+
+// picks up the items, starting in room `from`, and returns the room the droid ends up in
+pub fn collect(
+    droid: &mut Droid,
+    graph: &RoomGraph,
+    from: &str,
+    items: &[String],
+) -> Result<String, &'static str> {
+    let mut current = from.to_string();
+    for item in items {
+        let room = graph
+            .rooms
+            .values()
+            .find(|room| room.items.contains(item))
+            .ok_or("item is not on the map")?;
+        droid.walk(
+            &graph
+                .path(&current, &room.name)
+                .ok_or("room is unreachable")?,
+        )?;
+        let response = droid.command(&format!("take {}", item))?;
+        if response.halted {
+            return Err("droid halted while taking an item");
+        }
+        current = room.name.clone();
+    }
+    Ok(current)
+}
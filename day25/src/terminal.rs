@@ -0,0 +1,172 @@
+// interactive runner for ASCII intcode programs
+//
+// Lines starting with '!' are handled by the runner instead of being sent to the program:
+//   !help            list the meta-commands
+//   !history         show the numbered commands sent so far
+//   !<n>             send command <n> from the history again
+//   !save <name>     snapshot the program under <name>
+//   !load <name>     restore the snapshot <name>, along with the history at that point
+//   !quit            stop the session
+
+use crate::ascii::{AsciiComputer, Event};
+use crate::emulator::Program;
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, BufRead, Write};
+
+const HELP: &str = "meta-commands: !help, !history, !<n>, !save <name>, !load <name>, !quit";
+
+struct Snapshot {
+    computer: AsciiComputer,
+    history_len: usize,
+}
+
+pub struct Terminal {
+    computer: AsciiComputer,
+    history: Vec<String>,
+    snapshots: HashMap<String, Snapshot>,
+    script: VecDeque<String>,
+    transcript: Option<Box<dyn Write>>,
+}
+
+enum Command {
+    Send(String),
+    Prompt,
+    Quit,
+}
+
+impl Terminal {
+    pub fn new(program: Program) -> Self {
+        Terminal {
+            computer: AsciiComputer::new(program),
+            history: Vec::new(),
+            snapshots: HashMap::new(),
+            script: VecDeque::new(),
+            transcript: None,
+        }
+    }
+
+    // commands to run before reading from the user
+    pub fn with_script(mut self, script: &str) -> Self {
+        self.script = script
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(String::from)
+            .collect();
+        self
+    }
+
+    pub fn with_transcript(mut self, transcript: impl Write + 'static) -> Self {
+        self.transcript = Some(Box::new(transcript));
+        self
+    }
+
+    // prints a line, and adds it to the transcript
+    fn show(&mut self, line: &str) -> io::Result<()> {
+        println!("{}", line);
+        if let Some(transcript) = &mut self.transcript {
+            writeln!(transcript, "{}", line)?;
+        }
+        Ok(())
+    }
+
+    fn read_line(&mut self, input: &mut impl BufRead) -> io::Result<Option<String>> {
+        if let Some(line) = self.script.pop_front() {
+            println!("{}", line);
+            return Ok(Some(line));
+        }
+
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        Ok(Some(line.trim_end_matches(&['\r', '\n'][..]).to_string()))
+    }
+
+    fn meta_command(&mut self, command: &str) -> io::Result<Command> {
+        let mut words = command.split_whitespace();
+        match (words.next(), words.next()) {
+            (Some("help"), None) => self.show(HELP)?,
+            (Some("history"), None) => {
+                let lines: Vec<String> = self
+                    .history
+                    .iter()
+                    .enumerate()
+                    .map(|(i, command)| format!("{:>4}  {}", i + 1, command))
+                    .collect();
+                for line in lines {
+                    self.show(&line)?;
+                }
+            }
+            (Some("save"), Some(name)) => {
+                let snapshot = Snapshot {
+                    computer: self.computer.clone(),
+                    history_len: self.history.len(),
+                };
+                self.snapshots.insert(name.to_string(), snapshot);
+                self.show(&format!("saved snapshot {}", name))?;
+            }
+            (Some("load"), Some(name)) => match self.snapshots.get(name) {
+                Some(snapshot) => {
+                    self.computer = snapshot.computer.clone();
+                    self.history.truncate(snapshot.history_len);
+                    self.show(&format!("loaded snapshot {}", name))?;
+                }
+                None => self.show(&format!("no snapshot named {}", name))?,
+            },
+            (Some("quit"), None) => return Ok(Command::Quit),
+            (Some(n), None) => match n.parse::<usize>() {
+                Ok(n) if n >= 1 && n <= self.history.len() => {
+                    let command = self.history[n - 1].clone();
+                    self.show(&command)?;
+                    return Ok(Command::Send(command));
+                }
+                _ => self.show(HELP)?,
+            },
+            _ => self.show(HELP)?,
+        }
+        Ok(Command::Prompt)
+    }
+
+    pub fn run(&mut self, input: &mut impl BufRead) -> Result<(), &'static str> {
+        loop {
+            for event in self.computer.read_until_input()? {
+                let res = match event {
+                    Event::Line(line) => self.show(&line),
+                    Event::NonAscii(out) => self.show(&format!("Non-ASCII Output: {}", out)),
+                    Event::NeedsInput => Ok(()),
+                    Event::Halted => return Ok(()),
+                };
+                res.map_err(|_| "failed to write output")?;
+            }
+
+            loop {
+                print!("> ");
+                io::stdout().flush().map_err(|_| "failed to write output")?;
+                let line = match self.read_line(input).map_err(|_| "failed to read input")? {
+                    Some(line) => line,
+                    None => return Ok(()),
+                };
+                if let Some(transcript) = &mut self.transcript {
+                    writeln!(transcript, "> {}", line).map_err(|_| "failed to write transcript")?;
+                }
+
+                let command = match line.strip_prefix('!') {
+                    Some(meta) => self
+                        .meta_command(meta)
+                        .map_err(|_| "failed to write output")?,
+                    None => Command::Send(line),
+                };
+                match command {
+                    Command::Send(line) => {
+                        self.computer.send_line(&line);
+                        self.history.push(line);
+                        break;
+                    }
+                    Command::Prompt => {}
+                    Command::Quit => return Ok(()),
+                }
+            }
+        }
+    }
+}